@@ -59,7 +59,7 @@ impl<'a, 'de: 'a, T: Archiveable + 'a> serde::de::Deserialize<'de> for Entry<'a,
                     })
                     .ok_or_else(|| serde::de::Error::missing_field("request"))?;
 
-                let request_params = T::RequestParams::parse_request(&request)
+                let mut request_params = T::RequestParams::parse_request(&request)
                     .map_err(|error| error.serde(&request))?;
 
                 let response = T::deserialize_response_field(&request_params, &mut map)?
@@ -72,16 +72,36 @@ impl<'a, 'de: 'a, T: Archiveable + 'a> serde::de::Deserialize<'de> for Entry<'a,
                     })
                     .ok_or_else(|| serde::de::Error::missing_field("response"))?;
 
-                match map.next_entry::<Cow<'_, str>, serde::de::IgnoredAny>()? {
-                    Some((field, _)) => Err(serde::de::Error::unknown_field(
-                        &field,
-                        &["request", "response"],
-                    )),
-                    None => Ok(Entry {
+                if let Some(data) = T::response_json(&response) {
+                    let response = response.with_data(data);
+
+                    request_params = T::RequestParams::parse_request_with_response(
                         request_params,
-                        exchange: Exchange { request, response },
-                    }),
+                        &request,
+                        &response,
+                    )
+                    .map_err(|error| error.serde(&request))?;
+                }
+
+                let mut exchange = Exchange::new(request, response);
+
+                while let Some(field) = map.next_key::<Cow<'_, str>>()? {
+                    match field.as_ref() {
+                        "tags" => exchange.tags = map.next_value()?,
+                        "duration_ms" => exchange.duration_ms = map.next_value()?,
+                        _ => {
+                            return Err(serde::de::Error::unknown_field(
+                                &field,
+                                &["request", "response", "tags", "duration_ms"],
+                            ));
+                        }
+                    }
                 }
+
+                Ok(Entry {
+                    request_params,
+                    exchange,
+                })
             }
         }
 
@@ -93,6 +113,7 @@ impl<'a, 'de: 'a, T: Archiveable + 'a> serde::de::Deserialize<'de> for Entry<'a,
 mod tests {
     use super::{Archiveable, Entry, Field};
     use crate::exchange::Response;
+    use crate::request::params::Params;
     use regex::Regex;
     use std::borrow::Cow;
     use std::sync::LazyLock;
@@ -113,11 +134,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn deserialize_google_archive_with_tags() -> Result<(), Box<dyn std::error::Error>> {
+        let mut value = serde_json::from_str::<serde_json::Value>(GOOGLE_PLAY_01_EXAMPLE)?;
+        value["tags"] = serde_json::json!(["reviews"]);
+        let json = serde_json::to_string(&value)?;
+
+        let archive = serde_json::from_str::<Entry<'_, GoogleData>>(&json)?;
+
+        assert_eq!(archive.exchange.tags, vec!["reviews"]);
+
+        value["other"] = serde_json::json!(1);
+        let json = serde_json::to_string(&value)?;
+
+        assert!(serde_json::from_str::<Entry<'_, GoogleData>>(&json).is_err());
+
+        Ok(())
+    }
+
     struct ReviewRequest<'a> {
         pagination: Pagination<'a>,
         review: Review,
     }
 
+    #[allow(clippy::elidable_lifetime_names, clippy::unnecessary_lazy_evaluations)]
     impl<'a> crate::request::params::Params for ReviewRequest<'a> {
         fn build_request(
             &self,
@@ -152,6 +192,7 @@ mod tests {
         Review(serde_json::Value),
     }
 
+    #[allow(clippy::redundant_closure, clippy::use_self)]
     impl Archiveable for GoogleData {
         type RequestParams = ReviewRequest<'static>;
 
@@ -165,6 +206,248 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deserialize_multiple_response_shapes() -> Result<(), Box<dyn std::error::Error>> {
+        let list = r#"{
+            "request": { "url": "https://example.com/items", "timestamp_ms": "1735689600000" },
+            "response": { "data": [{ "id": 1 }, { "id": 2 }] }
+        }"#;
+        let item = r#"{
+            "request": { "url": "https://example.com/items/1", "timestamp_ms": "1735689600000" },
+            "response": { "data": { "id": 1, "name": "first" } }
+        }"#;
+
+        let list = serde_json::from_str::<Entry<'_, ItemData>>(list)?;
+        let item = serde_json::from_str::<Entry<'_, ItemData>>(item)?;
+
+        assert_eq!(list.request_params, ItemRequest::List);
+        assert_eq!(
+            list.exchange.response.data,
+            ItemData::List(vec![ItemSummary { id: 1 }, ItemSummary { id: 2 }])
+        );
+        assert_eq!(item.request_params, ItemRequest::Item(1));
+        assert_eq!(
+            item.exchange.response.data,
+            ItemData::Item(ItemDetail {
+                id: 1,
+                name: "first".to_string()
+            })
+        );
+
+        // The shape is chosen by the request, so a mismatched response is an error.
+        let mismatched = r#"{
+            "request": { "url": "https://example.com/items/1", "timestamp_ms": "1735689600000" },
+            "response": { "data": [{ "id": 1 }] }
+        }"#;
+
+        assert!(serde_json::from_str::<Entry<'_, ItemData>>(mismatched).is_err());
+
+        // Built requests parse back to the same parameters.
+        for params in [ItemRequest::List, ItemRequest::Item(1)] {
+            let request = params.build_request(None);
+
+            assert_eq!(ItemRequest::parse_request(&request)?, params);
+        }
+
+        Ok(())
+    }
+
+    #[derive(Debug, Eq, PartialEq)]
+    enum ItemRequest {
+        List,
+        Item(u64),
+    }
+
+    impl crate::request::params::Params for ItemRequest {
+        fn build_request(
+            &self,
+            timestamp: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> crate::request::Request<'_> {
+            let url = match self {
+                Self::List => "https://example.com/items".to_string(),
+                Self::Item(id) => format!("https://example.com/items/{id}"),
+            };
+
+            crate::request::Request::new(
+                url,
+                timestamp,
+                None,
+                None::<[(&str, &str); 0]>,
+                None::<&str>,
+            )
+            .expect("valid item URL")
+        }
+
+        fn parse_request(
+            request: &crate::request::Request<'_>,
+        ) -> Result<Self, crate::request::params::ParseError> {
+            match request.url.path().strip_prefix("/items") {
+                Some("") => Ok(Self::List),
+                Some(id) => id
+                    .strip_prefix('/')
+                    .and_then(|id| id.parse().ok())
+                    .map(Self::Item)
+                    .ok_or(crate::request::params::ParseError::InvalidUrl {
+                        expected: "item request",
+                    }),
+                None => Err(crate::request::params::ParseError::InvalidUrl {
+                    expected: "item request",
+                }),
+            }
+        }
+    }
+
+    #[derive(Debug, Eq, PartialEq, serde::Deserialize)]
+    struct ItemSummary {
+        id: u64,
+    }
+
+    #[derive(Debug, Eq, PartialEq, serde::Deserialize)]
+    struct ItemDetail {
+        id: u64,
+        name: String,
+    }
+
+    #[derive(Debug, Eq, PartialEq)]
+    enum ItemData {
+        List(Vec<ItemSummary>),
+        Item(ItemDetail),
+    }
+
+    impl Archiveable for ItemData {
+        type RequestParams = ItemRequest;
+
+        fn deserialize_response_field<'de, A: serde::de::MapAccess<'de>>(
+            request_params: &Self::RequestParams,
+            map: &mut A,
+        ) -> Result<Option<(Field, Response<'de, Self>)>, A::Error> {
+            match request_params {
+                ItemRequest::List => crate::archive::next_response_field(map, Self::List),
+                ItemRequest::Item(_) => crate::archive::next_response_field(map, Self::Item),
+            }
+        }
+    }
+
+    #[test]
+    fn deserialize_with_response_params() -> Result<(), Box<dyn std::error::Error>> {
+        let json = r#"{
+            "request": { "url": "https://example.com/echo", "timestamp_ms": "1735689600000" },
+            "response": { "data": { "request_id": "abc" }, "status": 202 }
+        }"#;
+
+        let entry = serde_json::from_str::<Entry<'_, EchoData>>(json)?;
+
+        assert_eq!(
+            entry.request_params,
+            EchoRequest {
+                request_id: Some("abc".to_string()),
+                status: Some(202),
+            }
+        );
+        assert_eq!(
+            entry.exchange.response.status,
+            Some(http::StatusCode::ACCEPTED)
+        );
+
+        // Without a JSON view of the response, only the request is used.
+        let json = r#"{
+            "request": { "url": "https://example.com/echo", "timestamp_ms": "1735689600000" },
+            "response": { "data": { "request_id": null }, "status": 202 }
+        }"#;
+
+        let entry = serde_json::from_str::<Entry<'_, EchoData>>(json)?;
+
+        assert_eq!(
+            entry.request_params,
+            EchoRequest {
+                request_id: None,
+                status: None,
+            }
+        );
+
+        // The request must be parsed on its own before the response is read, so a failure there
+        // is fatal even if the response would have completed the parameters.
+        let json = r#"{
+            "request": { "url": "https://example.com/other", "timestamp_ms": "1735689600000" },
+            "response": { "data": { "request_id": "abc" }, "status": 202 }
+        }"#;
+
+        assert!(serde_json::from_str::<Entry<'_, EchoData>>(json).is_err());
+
+        Ok(())
+    }
+
+    #[derive(Debug, Eq, PartialEq)]
+    struct EchoRequest {
+        request_id: Option<String>,
+        status: Option<u16>,
+    }
+
+    impl crate::request::params::Params for EchoRequest {
+        fn build_request(
+            &self,
+            _timestamp: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> crate::request::Request<'_> {
+            crate::request::Request::builder("https://example.com/echo")
+                .build()
+                .unwrap()
+        }
+
+        fn parse_request(
+            request: &crate::request::Request<'_>,
+        ) -> Result<Self, crate::request::params::ParseError> {
+            // The request ID and status are only known once the response has been read.
+            if request.url.path() == "/echo" {
+                Ok(Self {
+                    request_id: None,
+                    status: None,
+                })
+            } else {
+                Err(crate::request::params::ParseError::InvalidUrl {
+                    expected: "echo URL",
+                })
+            }
+        }
+
+        fn parse_request_with_response(
+            provisional: Self,
+            _request: &crate::request::Request<'_>,
+            response: &Response<'_, serde_json::Value>,
+        ) -> Result<Self, crate::request::params::ParseError> {
+            Ok(Self {
+                request_id: response.data["request_id"].as_str().map(str::to_string),
+                status: response
+                    .status
+                    .map(|status| status.as_u16())
+                    .or(provisional.status),
+            })
+        }
+    }
+
+    #[derive(Debug, Eq, PartialEq, serde::Deserialize)]
+    struct EchoData {
+        request_id: Option<String>,
+    }
+
+    impl Archiveable for EchoData {
+        type RequestParams = EchoRequest;
+
+        fn deserialize_response_field<'de, A: serde::de::MapAccess<'de>>(
+            _request_params: &Self::RequestParams,
+            map: &mut A,
+        ) -> Result<Option<(Field, Response<'de, Self>)>, A::Error> {
+            crate::archive::next_response_field(map, std::convert::identity)
+        }
+
+        fn response_json(response: &Response<'_, Self>) -> Option<serde_json::Value> {
+            response
+                .data
+                .request_id
+                .as_ref()
+                .map(|request_id| serde_json::json!({ "request_id": request_id }))
+        }
+    }
+
     #[derive(Clone, Debug, Eq, PartialEq)]
     struct Pagination<'a> {
         pub language: Cow<'a, str>,
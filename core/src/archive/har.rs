@@ -0,0 +1,488 @@
+//! Conversion between exchanges and the HTTP Archive (HAR) 1.2 format.
+
+use crate::{exchange::Exchange, multi_value::MultiValue, request::Request};
+use serde_json::{Value, json};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HarError {
+    #[error("Missing HAR log entries")]
+    MissingEntries,
+    /// A field of an entry is missing or has an invalid value.
+    #[error("Invalid HAR entry")]
+    InvalidEntry { index: usize, field: &'static str },
+    #[error("Invalid HAR entry URL")]
+    Url {
+        index: usize,
+        #[source]
+        source: url::ParseError,
+    },
+    #[error("Invalid HAR entry start time")]
+    StartedDateTime {
+        index: usize,
+        #[source]
+        source: chrono::ParseError,
+    },
+}
+
+impl HarError {
+    /// The index of the entry the error occurred in, if any.
+    #[must_use]
+    pub const fn index(&self) -> Option<usize> {
+        match self {
+            Self::MissingEntries => None,
+            Self::InvalidEntry { index, .. }
+            | Self::Url { index, .. }
+            | Self::StartedDateTime { index, .. } => Some(*index),
+        }
+    }
+}
+
+const HAR_VERSION: &str = "1.2";
+
+/// Build a HAR `log` object with an entry for each exchange.
+///
+/// Header names are kept as they were archived, and a header with several values becomes several
+/// HAR headers. The response content is the raw body if it was kept, and otherwise the data (as
+/// JSON, unless it is a string). Archives do not record timing phases, so the whole duration (if
+/// any) is reported as waiting time.
+pub fn to_har<'a, T: serde::Serialize, I: IntoIterator<Item = Exchange<'a, T>>>(
+    exchanges: I,
+) -> Result<Value, serde_json::Error> {
+    let entries = exchanges
+        .into_iter()
+        .map(|exchange| entry(&exchange))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(json!({
+        "log": {
+            "version": HAR_VERSION,
+            "creator": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "entries": entries,
+        }
+    }))
+}
+
+fn entry<T: serde::Serialize>(exchange: &Exchange<'_, T>) -> Result<Value, serde_json::Error> {
+    let request = &exchange.request;
+    let response = &exchange.response;
+    let http_version = response
+        .http_version
+        .and_then(crate::exchange::http_version::name)
+        .unwrap_or_default();
+
+    let (text, is_json) = match response.raw.as_deref() {
+        Some(raw) => (raw.to_string(), false),
+        None => match serde_json::to_value(&response.data)? {
+            Value::String(value) => (value, false),
+            value => (serde_json::to_string(&value)?, true),
+        },
+    };
+
+    let mime_type = response.header("content-type").map_or_else(
+        || if is_json { "application/json" } else { "" }.to_string(),
+        |values| values.first.to_string(),
+    );
+
+    // Archives without a status only contain successful responses.
+    let status = response.status.unwrap_or(http::StatusCode::OK);
+    let time = exchange.duration_ms.unwrap_or_default();
+
+    Ok(json!({
+        "startedDateTime": request
+            .timestamp
+            .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        "time": time,
+        "request": har_request(request, http_version),
+        "response": {
+            "status": status.as_u16(),
+            "statusText": status.canonical_reason().unwrap_or_default(),
+            "httpVersion": http_version,
+            "cookies": response
+                .set_cookies()
+                .into_iter()
+                .map(har_cookie)
+                .collect::<Vec<_>>(),
+            "headers": response
+                .headers
+                .iter()
+                .flat_map(|(name, values)| values.iter().map(move |value| name_value(name, &value)))
+                .collect::<Vec<_>>(),
+            "content": {
+                "size": text.len(),
+                "mimeType": mime_type,
+                "text": text,
+            },
+            "redirectURL": response
+                .header("location")
+                .map(|values| values.first.to_string())
+                .unwrap_or_default(),
+            "headersSize": -1,
+            "bodySize": -1,
+        },
+        "cache": {},
+        "timings": {
+            "send": 0,
+            "wait": time,
+            "receive": 0,
+        },
+    }))
+}
+
+fn har_request(request: &crate::request::Request<'_>, http_version: &str) -> Value {
+    let mut har_request = json!({
+        "method": request.method.as_str(),
+        "url": request.url.as_str(),
+        "httpVersion": http_version,
+        "cookies": request
+            .cookies()
+            .into_iter()
+            .map(|(name, value)| name_value(&name, &value))
+            .collect::<Vec<_>>(),
+        "headers": request
+            .headers
+            .iter()
+            .map(|(name, value)| name_value(name, value))
+            .collect::<Vec<_>>(),
+        "queryString": request
+            .url
+            .query_pairs()
+            .map(|(name, value)| name_value(&name, &value))
+            .collect::<Vec<_>>(),
+        "headersSize": -1,
+        "bodySize": request.body.as_ref().map_or(0, |body| body.len()),
+    });
+
+    if let Some(body) = &request.body {
+        har_request["postData"] = json!({
+            "mimeType": request.header("content-type").unwrap_or_default(),
+            "text": body,
+        });
+    }
+
+    har_request
+}
+
+fn har_cookie(cookie: crate::cookie::Cookie) -> Value {
+    let mut har_cookie = json!({
+        "name": cookie.name,
+        "value": cookie.value,
+        "httpOnly": cookie.http_only,
+        "secure": cookie.secure,
+    });
+
+    if let Some(path) = cookie.path {
+        har_cookie["path"] = path.into();
+    }
+
+    if let Some(domain) = cookie.domain {
+        har_cookie["domain"] = domain.into();
+    }
+
+    if let Some(expires) = cookie.expires {
+        har_cookie["expires"] = expires.to_rfc3339().into();
+    }
+
+    har_cookie
+}
+
+/// Read the entries of a HAR `log` object as exchanges.
+///
+/// HTTP/2 pseudo-headers (e.g. `:authority`) are dropped. Repeated request headers are joined
+/// (with `; ` for `Cookie` and `, ` otherwise), and repeated response headers are collected into a
+/// single [`MultiValue`]. The response content is parsed as JSON if its MIME type is a JSON type
+/// (falling back to a string if it is not valid JSON), and is otherwise kept as a string (in
+/// particular, base64-encoded content is not decoded). A status of 0, which browsers use for
+/// failed requests, is not recorded.
+pub fn from_har(value: &Value) -> Result<Vec<Exchange<'static, Value>>, HarError> {
+    value
+        .pointer("/log/entries")
+        .and_then(Value::as_array)
+        .ok_or(HarError::MissingEntries)?
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| from_har_entry(index, entry))
+        .collect()
+}
+
+fn from_har_entry(index: usize, entry: &Value) -> Result<Exchange<'static, Value>, HarError> {
+    let invalid = |field| HarError::InvalidEntry { index, field };
+    let string = |pointer: &str, field| {
+        entry
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid(field))
+    };
+
+    let timestamp =
+        chrono::DateTime::parse_from_rfc3339(string("/startedDateTime", "startedDateTime")?)
+            .map_err(|source| HarError::StartedDateTime { index, source })?
+            .with_timezone(&chrono::Utc);
+
+    let method = http::Method::from_bytes(string("/request/method", "request.method")?.as_bytes())
+        .map_err(|_| invalid("request.method"))?;
+
+    let mut headers = indexmap::IndexMap::<Cow<'static, str>, Cow<'static, str>>::new();
+
+    for (name, value) in
+        name_values(entry.pointer("/request/headers")).ok_or_else(|| invalid("request.headers"))?
+    {
+        if let Some(existing) = headers.get_mut(name) {
+            let separator = if name.eq_ignore_ascii_case("cookie") {
+                "; "
+            } else {
+                ", "
+            };
+
+            existing.to_mut().push_str(separator);
+            existing.to_mut().push_str(value);
+        } else {
+            headers.insert(name.to_string().into(), value.to_string().into());
+        }
+    }
+
+    let request = Request::new(
+        string("/request/url", "request.url")?,
+        Some(timestamp),
+        Some(method),
+        Some(headers),
+        entry
+            .pointer("/request/postData/text")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    )
+    .map_err(|source| HarError::Url { index, source })?;
+
+    let mut response_headers = HashMap::<Cow<'static, str>, MultiValue<'static>>::new();
+
+    for (name, value) in name_values(entry.pointer("/response/headers"))
+        .ok_or_else(|| invalid("response.headers"))?
+    {
+        match response_headers.get_mut(name) {
+            Some(values) => values.push(value.to_string()),
+            None => {
+                response_headers
+                    .insert(name.to_string().into(), MultiValue::new(value.to_string()));
+            }
+        }
+    }
+
+    let status = match entry.pointer("/response/status").and_then(Value::as_u64) {
+        Some(0) => None,
+        Some(status) => Some(
+            u16::try_from(status)
+                .ok()
+                .and_then(|status| http::StatusCode::from_u16(status).ok())
+                .ok_or_else(|| invalid("response.status"))?,
+        ),
+        None => return Err(invalid("response.status")),
+    };
+
+    let text = entry
+        .pointer("/response/content/text")
+        .and_then(Value::as_str);
+    let is_json = entry
+        .pointer("/response/content/mimeType")
+        .and_then(Value::as_str)
+        .is_some_and(crate::header::is_json_mime_type);
+    let data = text.map_or(Value::Null, |text| {
+        is_json
+            .then(|| serde_json::from_str(text).ok())
+            .flatten()
+            .unwrap_or_else(|| Value::String(text.to_string()))
+    });
+
+    let mut response = crate::exchange::Response::new(response_headers, data);
+    response.status = status;
+    response.http_version = entry
+        .pointer("/response/httpVersion")
+        .and_then(Value::as_str)
+        .and_then(parse_http_version);
+
+    let mut exchange = Exchange::new(request, response);
+    exchange.duration_ms = entry
+        .get("time")
+        .and_then(Value::as_f64)
+        .and_then(duration_ms);
+
+    Ok(exchange)
+}
+
+/// The name-value pairs in a HAR array, skipping HTTP/2 pseudo-headers.
+fn name_values(value: Option<&Value>) -> Option<Vec<(&str, &str)>> {
+    value?
+        .as_array()?
+        .iter()
+        .map(|pair| Some((pair.get("name")?.as_str()?, pair.get("value")?.as_str()?)))
+        .filter(|pair| pair.is_none_or(|(name, _)| !name.starts_with(':')))
+        .collect()
+}
+
+fn parse_http_version(value: &str) -> Option<http::Version> {
+    match value.to_ascii_uppercase().as_str() {
+        "HTTP/0.9" => Some(http::Version::HTTP_09),
+        "HTTP/1.0" => Some(http::Version::HTTP_10),
+        "HTTP/1.1" => Some(http::Version::HTTP_11),
+        "HTTP/2" | "HTTP/2.0" | "H2" => Some(http::Version::HTTP_2),
+        "HTTP/3" | "HTTP/3.0" | "H3" => Some(http::Version::HTTP_3),
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn duration_ms(time: f64) -> Option<u64> {
+    (time.is_finite() && time >= 0.0).then(|| time.round() as u64)
+}
+
+/// A HAR name-value pair (as used for headers, cookies, and query parameters).
+fn name_value(name: &str, value: &str) -> Value {
+    json!({ "name": name, "value": value })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exchange::Exchange;
+
+    const APPLE_ITUNES_01_EXAMPLE: &str = include_str!("../../../examples/apple-itunes-01.json");
+
+    #[test]
+    fn to_har() -> Result<(), Box<dyn std::error::Error>> {
+        let mut exchange: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+        exchange.duration_ms = Some(120);
+
+        let mut values = crate::multi_value::MultiValue::new("a=1; Path=/");
+        values.push("b=2; Secure");
+        exchange
+            .response
+            .headers
+            .insert("Set-Cookie".into(), values);
+
+        let har = super::to_har([exchange.clone()])?;
+        let entry = &har["log"]["entries"][0];
+
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(entry["time"], 120);
+        assert_eq!(entry["request"]["method"], "GET");
+        assert_eq!(entry["request"]["url"], exchange.request.url.as_str());
+        assert_eq!(
+            entry["request"]["queryString"].as_array().map(Vec::len),
+            Some(exchange.request.url.query_pairs().count())
+        );
+        assert_eq!(
+            entry["startedDateTime"],
+            exchange
+                .request
+                .timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+        );
+
+        let set_cookie_headers = entry["response"]["headers"]
+            .as_array()
+            .ok_or("missing headers")?
+            .iter()
+            .filter(|header| header["name"] == "Set-Cookie")
+            .count();
+
+        assert_eq!(set_cookie_headers, 2);
+        assert_eq!(entry["response"]["cookies"][1]["secure"], true);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(
+                entry["response"]["content"]["text"]
+                    .as_str()
+                    .ok_or("missing content")?
+            )?,
+            exchange.response.data
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_har() -> Result<(), Box<dyn std::error::Error>> {
+        let mut exchange: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+        exchange.duration_ms = Some(120);
+        exchange.response.status = Some(http::StatusCode::OK);
+        exchange.response.http_version = Some(http::Version::HTTP_2);
+
+        let mut values = crate::multi_value::MultiValue::new("a=1");
+        values.push("b=2");
+        exchange
+            .response
+            .headers
+            .insert("Set-Cookie".into(), values);
+
+        // The original content type is `text/javascript`.
+        exchange.response.headers.insert(
+            "content-type".into(),
+            crate::multi_value::MultiValue::new("application/json; charset=utf-8"),
+        );
+
+        let imported = super::from_har(&super::to_har([exchange.clone()])?)?;
+
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].request, exchange.request);
+        assert_eq!(imported[0].response.data, exchange.response.data);
+        assert_eq!(imported[0].response.headers, exchange.response.headers);
+        assert_eq!(imported[0].response.status, exchange.response.status);
+        assert_eq!(
+            imported[0].response.http_version,
+            exchange.response.http_version
+        );
+        assert_eq!(imported[0].duration_ms, Some(120));
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_har_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let entry = serde_json::json!({
+            "startedDateTime": "2025-01-01T00:00:00.000Z",
+            "time": 1.5,
+            "request": {
+                "method": "POST",
+                "url": "https://example.com/",
+                "headers": [
+                    { "name": ":authority", "value": "example.com" },
+                    { "name": "Cookie", "value": "a=1" },
+                    { "name": "Cookie", "value": "b=2" }
+                ],
+                "postData": { "mimeType": "text/plain", "text": "abc" }
+            },
+            "response": {
+                "status": 0,
+                "headers": [],
+                "content": { "mimeType": "application/problem+json", "text": "{\"a\":1}" }
+            }
+        });
+
+        let exchanges = super::from_har(&serde_json::json!({ "log": { "entries": [entry] } }))?;
+
+        assert_eq!(exchanges[0].request.header("cookie"), Some("a=1; b=2"));
+        assert_eq!(exchanges[0].request.headers.len(), 1);
+        assert_eq!(exchanges[0].request.body.as_deref(), Some("abc"));
+        assert_eq!(exchanges[0].response.data, serde_json::json!({ "a": 1 }));
+        assert_eq!(exchanges[0].response.status, None);
+        assert_eq!(exchanges[0].duration_ms, Some(2));
+
+        let mut invalid = entry.clone();
+        invalid["request"]["url"] = "not a url".into();
+
+        let error = super::from_har(&serde_json::json!({ "log": { "entries": [entry, invalid] } }))
+            .unwrap_err();
+
+        assert!(matches!(error, super::HarError::Url { index: 1, .. }));
+        assert_eq!(error.index(), Some(1));
+        assert!(matches!(
+            super::from_har(&serde_json::json!({})),
+            Err(super::HarError::MissingEntries)
+        ));
+
+        Ok(())
+    }
+}
@@ -1,13 +1,61 @@
 use crate::{archive::entry::Field, exchange::Response};
 
 pub mod entry;
+pub mod har;
+#[cfg(feature = "parquet")]
+mod parquet;
 pub mod store;
+mod tar;
 
 pub trait Archiveable: Sized {
     type RequestParams: crate::request::params::Params;
 
+    /// Deserialize the next map entry, which should be the response.
+    ///
+    /// The request parameters have already been parsed, so types whose response data has several
+    /// shapes can use them to choose how to deserialize it (typically with
+    /// [`next_response_field`], wrapping each shape in a different variant of `Self`).
     fn deserialize_response_field<'de, A: serde::de::MapAccess<'de>>(
         request_params: &Self::RequestParams,
         map: &mut A,
     ) -> Result<Option<(Field, Response<'de, Self>)>, A::Error>;
+
+    /// A JSON view of the response data, for request parameter types that need the response to
+    /// be fully parsed (see [`crate::request::params::Params::parse_request_with_response`]).
+    ///
+    /// If this returns a value, archive entries pass it along with the parameters parsed from the
+    /// request alone to [`crate::request::params::Params::parse_request_with_response`]. Since
+    /// the data has already been deserialized as `Self`, implementations need to encode it back
+    /// to JSON by hand, which happens for every entry read, so it should only include what the
+    /// request parameters need. The default implementation returns `None`.
+    fn response_json(_response: &Response<'_, Self>) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+/// Deserialize the next map entry as a response with data of type `U`, converting the data with
+/// `f`.
+///
+/// This is a helper for implementing [`Archiveable::deserialize_response_field`]. For example, if
+/// the request parameters indicate whether a response is a list or a single item:
+///
+/// ```ignore
+/// match request_params {
+///     Params::List { .. } => next_response_field(map, Data::List),
+///     Params::Item { .. } => next_response_field(map, Data::Item),
+/// }
+/// ```
+pub fn next_response_field<
+    'de,
+    A: serde::de::MapAccess<'de>,
+    U: serde::de::Deserialize<'de>,
+    T,
+    F: FnOnce(U) -> T,
+>(
+    map: &mut A,
+    f: F,
+) -> Result<Option<(Field, Response<'de, T>)>, A::Error> {
+    Ok(map
+        .next_entry::<Field, Response<'de, U>>()?
+        .map(|(field, response)| (field, response.map(f))))
 }
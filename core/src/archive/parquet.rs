@@ -0,0 +1,128 @@
+use super::store::{Error, Store, parse_exchange};
+use arrow_array::{ArrayRef, RecordBatch, StringArray, TimestampMillisecondArray, UInt16Array};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use std::path::Path;
+use std::sync::Arc;
+
+const BATCH_SIZE: usize = 1024;
+
+impl Store {
+    /// Write every exchange as a row of a Parquet file.
+    ///
+    /// The request timestamp, method, URL, host, and response status are stored as columns, and
+    /// the response data as a JSON string. Exchanges are written in batches, so the whole store is
+    /// never held in memory. Returns the number of rows written.
+    pub fn export_parquet(&self, path: &Path) -> Result<usize, Error> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Millisecond, Some("+00:00".into())),
+                false,
+            ),
+            Field::new("method", DataType::Utf8, false),
+            Field::new("url", DataType::Utf8, false),
+            Field::new("host", DataType::Utf8, true),
+            Field::new("status", DataType::UInt16, true),
+            Field::new("data", DataType::Utf8, false),
+        ]));
+
+        let version = self.format_version()?;
+        let mut writer = parquet::arrow::ArrowWriter::try_new(
+            std::fs::File::create(path)?,
+            schema.clone(),
+            None,
+        )?;
+        let mut rows = Rows::default();
+        let mut count = 0;
+
+        for (_, contents) in self.contents(false)? {
+            let exchange = parse_exchange::<serde_json::Value>(version, contents)?;
+
+            rows.timestamps
+                .push(exchange.request.timestamp.timestamp_millis());
+            rows.methods.push(exchange.request.method.to_string());
+            rows.urls.push(exchange.request.url.to_string());
+            rows.hosts
+                .push(exchange.request.url.host_str().map(str::to_string));
+            rows.statuses
+                .push(exchange.response.status.map(|status| status.as_u16()));
+            rows.data
+                .push(serde_json::to_string(&exchange.response.data)?);
+
+            count += 1;
+
+            if rows.timestamps.len() == BATCH_SIZE {
+                writer.write(&rows.take_batch(&schema)?)?;
+            }
+        }
+
+        if !rows.timestamps.is_empty() {
+            writer.write(&rows.take_batch(&schema)?)?;
+        }
+
+        writer.close()?;
+
+        Ok(count)
+    }
+}
+
+#[derive(Default)]
+struct Rows {
+    timestamps: Vec<i64>,
+    methods: Vec<String>,
+    urls: Vec<String>,
+    hosts: Vec<Option<String>>,
+    statuses: Vec<Option<u16>>,
+    data: Vec<String>,
+}
+
+impl Rows {
+    fn take_batch(
+        &mut self,
+        schema: &Arc<Schema>,
+    ) -> Result<RecordBatch, arrow_schema::ArrowError> {
+        let rows = std::mem::take(self);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampMillisecondArray::from(rows.timestamps).with_timezone_utc()),
+            Arc::new(StringArray::from(rows.methods)),
+            Arc::new(StringArray::from(rows.urls)),
+            Arc::new(StringArray::from(rows.hosts)),
+            Arc::new(UInt16Array::from(rows.statuses)),
+            Arc::new(StringArray::from(rows.data)),
+        ];
+
+        RecordBatch::try_new(schema.clone(), columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::archive::store::testing::test_store;
+    use parquet::file::reader::FileReader;
+
+    #[test]
+    fn export_parquet() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("export-parquet")?;
+        store.save_examples(0..3, |_, _| {})?;
+
+        let output_store = test_store("export-parquet-output")?;
+        let output = output_store.base.join("exchanges.parquet");
+
+        assert_eq!(store.export_parquet(&output)?, 3);
+
+        let reader =
+            parquet::file::reader::SerializedFileReader::new(std::fs::File::open(&output)?)?;
+
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+        assert_eq!(
+            reader
+                .metadata()
+                .file_metadata()
+                .schema_descr()
+                .num_columns(),
+            6
+        );
+
+        Ok(())
+    }
+}
@@ -3,7 +3,16 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::archive::Archiveable;
+use crate::{
+    archive::Archiveable,
+    exchange::{
+        Compression, Exchange, Format, TEMP_EXTENSION, read_file, write_file, write_file_atomic,
+    },
+};
+use bounded_static::IntoBoundedStatic;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "tokio")]
+use futures::StreamExt;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -11,26 +20,135 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("JSON error")]
     Json(#[from] serde_json::Error),
+    #[error("Client error")]
+    Client(#[from] crate::client::Error),
+    #[error("Invalid format version")]
+    InvalidFormatVersion(String),
+    #[error("Invalid manifest line")]
+    InvalidManifestLine(String),
+    #[cfg(feature = "parquet")]
+    #[error("Arrow error")]
+    Arrow(#[from] arrow_schema::ArrowError),
+    #[cfg(feature = "parquet")]
+    #[error("Parquet error")]
+    Parquet(#[from] parquet::errors::ParquetError),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Store {
     pub base: PathBuf,
+    sequence: Sequence,
+    fingerprints: Fingerprints,
 }
 
 impl Store {
     pub fn new<P: AsRef<Path>>(base: P) -> Self {
         Self {
             base: base.as_ref().to_path_buf(),
+            sequence: Sequence::default(),
+            fingerprints: Fingerprints::default(),
         }
     }
 
+    /// Open a store, creating the directory if necessary and checking that it is writable.
+    pub fn open_or_create<P: AsRef<Path>>(base: P) -> Result<Self, std::io::Error> {
+        let store = Self::new(base);
+
+        std::fs::create_dir_all(&store.base)?;
+
+        let probe_path = store
+            .base
+            .join(format!(".scraper-trail-probe-{}", std::process::id()));
+
+        std::fs::write(&probe_path, [])
+            .and_then(|()| std::fs::remove_file(&probe_path))
+            .map_err(|error| {
+                std::io::Error::new(
+                    error.kind(),
+                    format!(
+                        "store directory {} is not writable: {error}",
+                        store.base.display()
+                    ),
+                )
+            })?;
+
+        Ok(store)
+    }
+
     pub fn paths(&self, reverse: bool) -> Result<Vec<PathBuf>, std::io::Error> {
         let mut paths = std::fs::read_dir(&self.base)?
             .map(|entry| entry.map(|entry| entry.path()))
             .collect::<Result<Vec<_>, _>>()?;
 
-        paths.sort();
+        paths.retain(|path| is_exchange_path(path));
+        paths.sort_by(|a, b| compare_paths(a, b));
+
+        if reverse {
+            paths.reverse();
+        }
+
+        Ok(paths)
+    }
+
+    /// List exchange files in the store directory and all of its subdirectories.
+    ///
+    /// If `follow_symlinks` is false, symbolic links are skipped entirely. Otherwise they are
+    /// resolved, and only included if their targets are inside the store directory. Each
+    /// directory is visited at most once, so symbolic link cycles cannot cause infinite loops.
+    pub fn paths_recursive(
+        &self,
+        reverse: bool,
+        follow_symlinks: bool,
+    ) -> Result<Vec<PathBuf>, std::io::Error> {
+        let canonical_base = self.base.canonicalize()?;
+        let mut visited = std::collections::HashSet::from([canonical_base.clone()]);
+        let mut directories = vec![self.base.clone()];
+        let mut paths = vec![];
+
+        while let Some(directory) = directories.pop() {
+            for entry in std::fs::read_dir(directory)? {
+                let path = entry?.path();
+                let metadata = std::fs::symlink_metadata(&path)?;
+
+                let is_dir = if metadata.is_symlink() {
+                    if !follow_symlinks {
+                        continue;
+                    }
+
+                    let target = match path.canonicalize() {
+                        Ok(target) if target.starts_with(&canonical_base) => target,
+                        // Broken links and links outside the store are skipped.
+                        _ => continue,
+                    };
+
+                    if target.is_dir() {
+                        if !visited.insert(target) {
+                            continue;
+                        }
+
+                        true
+                    } else {
+                        false
+                    }
+                } else if metadata.is_dir() {
+                    if !visited.insert(path.canonicalize()?) {
+                        continue;
+                    }
+
+                    true
+                } else {
+                    false
+                };
+
+                if is_dir {
+                    directories.push(path);
+                } else if is_exchange_path(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        paths.sort_by(|a, b| compare_paths(a, b));
 
         if reverse {
             paths.reverse();
@@ -39,6 +157,104 @@ impl Store {
         Ok(paths)
     }
 
+    /// The exchange paths whose file names have a timestamp in the half-open range from `start`
+    /// (inclusive) to `end` (exclusive).
+    ///
+    /// Only the file names are checked, and files whose names do not start with a timestamp are
+    /// skipped.
+    pub fn paths_in_range(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        reverse: bool,
+    ) -> Result<Vec<PathBuf>, std::io::Error> {
+        let start = start.map(|start| start.timestamp_millis());
+        let end = end.map(|end| end.timestamp_millis());
+        let mut paths = self.paths(reverse)?;
+
+        paths.retain(|path| {
+            numeric_key(path)
+                .and_then(|(timestamp, _)| i64::try_from(timestamp).ok())
+                .is_some_and(|timestamp| {
+                    start.is_none_or(|start| timestamp >= start)
+                        && end.is_none_or(|end| timestamp < end)
+                })
+        });
+
+        Ok(paths)
+    }
+
+    /// The number of exchange files in the store directory (the length of [`Self::paths`]).
+    ///
+    /// The paths are not collected or sorted.
+    pub fn count(&self) -> Result<usize, std::io::Error> {
+        std::fs::read_dir(&self.base)?.try_fold(0, |count, entry| {
+            Ok(count + usize::from(is_exchange_path(&entry?.path())))
+        })
+    }
+
+    /// Whether the store directory contains no exchange files.
+    pub fn is_empty(&self) -> Result<bool, std::io::Error> {
+        for entry in std::fs::read_dir(&self.base)? {
+            if is_exchange_path(&entry?.path()) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Read the most recent exchange, by the timestamp in its file name.
+    ///
+    /// Only that file is read, and files whose names do not start with a timestamp are ignored.
+    pub fn latest<T: serde::de::DeserializeOwned + 'static>(
+        &self,
+    ) -> Result<Option<(PathBuf, Exchange<'static, T>)>, Error> {
+        self.read_extreme(true)
+    }
+
+    /// Read the oldest exchange, by the timestamp in its file name (see [`Self::latest`]).
+    pub fn earliest<T: serde::de::DeserializeOwned + 'static>(
+        &self,
+    ) -> Result<Option<(PathBuf, Exchange<'static, T>)>, Error> {
+        self.read_extreme(false)
+    }
+
+    fn read_extreme<T: serde::de::DeserializeOwned + 'static>(
+        &self,
+        latest: bool,
+    ) -> Result<Option<(PathBuf, Exchange<'static, T>)>, Error> {
+        let mut extreme: Option<((u64, u64), PathBuf)> = None;
+
+        for entry in std::fs::read_dir(&self.base)? {
+            let path = entry?.path();
+
+            if let Some(key) = numeric_key(&path).filter(|_| is_exchange_path(&path))
+                && extreme.as_ref().is_none_or(|(extreme_key, extreme_path)| {
+                    let ordering = key
+                        .cmp(extreme_key)
+                        .then_with(|| path.file_name().cmp(&extreme_path.file_name()));
+
+                    if latest {
+                        ordering.is_gt()
+                    } else {
+                        ordering.is_lt()
+                    }
+                })
+            {
+                extreme = Some((key, path));
+            }
+        }
+
+        extreme
+            .map(|(_, path)| {
+                let exchange = parse_exchange(self.format_version()?, read_file(&path))?;
+
+                Ok((path, exchange))
+            })
+            .transpose()
+    }
+
     pub fn contents(&self, reverse: bool) -> Result<Contents, std::io::Error> {
         Ok(Contents {
             // We put the paths in reverse order, since we'll be popping them off the `Vec`.
@@ -46,6 +262,622 @@ impl Store {
         })
     }
 
+    /// Compute percentiles (from 0 to 100) of the recorded response times, in milliseconds.
+    ///
+    /// The result has a value for each requested percentile, in the same order, using the
+    /// nearest-rank method (so each value is an actual recorded time). Exchanges without a
+    /// recorded duration are ignored, and if there are none the result is empty.
+    pub fn latency_percentiles(&self, percentiles: &[f64]) -> Result<Vec<(f64, u64)>, Error> {
+        let mut durations = vec![];
+
+        for (_, contents) in self.contents(false)? {
+            let contents = contents?;
+            let exchange = serde_json::from_str::<Exchange<'_, serde::de::IgnoredAny>>(&contents)?;
+
+            durations.extend(exchange.duration_ms);
+        }
+
+        durations.sort_unstable();
+
+        Ok(if durations.is_empty() {
+            vec![]
+        } else {
+            percentiles
+                .iter()
+                .map(|&percentile| {
+                    (
+                        percentile,
+                        durations[nearest_rank(percentile, durations.len()) - 1],
+                    )
+                })
+                .collect()
+        })
+    }
+
+    /// Check whether the store contains an exchange for the given request.
+    ///
+    /// Requests are compared by [`crate::request::Request::cache_key`], so timestamps are ignored.
+    ///
+    /// The first call reads every file in the store to build a set of request fingerprints, which
+    /// is shared by clones of this store and updated by [`Self::write_if_new`]. Files that cannot
+    /// be read or parsed are skipped, and files added to the directory in other ways after the set
+    /// is built are not seen.
+    pub fn contains_request(&self, request: &crate::request::Request<'_>) -> Result<bool, Error> {
+        let fingerprints = self.fingerprints()?;
+        let contains = fingerprints
+            .as_ref()
+            .is_some_and(|fingerprints| fingerprints.contains(&fingerprint(request)));
+        drop(fingerprints);
+
+        Ok(contains)
+    }
+
+    /// Write the exchange only if the store does not already contain its request (see
+    /// [`Self::contains_request`]).
+    ///
+    /// Returns the path of the new file, or `None` if the request was already captured. The file
+    /// is written with [`Self::write_exchange_unique`], so it never replaces an existing file, and
+    /// the request is only recorded as captured once the write succeeds.
+    pub fn write_if_new<T: serde::Serialize>(
+        &self,
+        exchange: &Exchange<'_, T>,
+    ) -> Result<Option<PathBuf>, Error> {
+        let fingerprint = fingerprint(&exchange.request);
+
+        // The lock is held while writing, so that concurrent writes of the same request from
+        // clones of this store cannot both succeed.
+        let mut fingerprints = self.fingerprints()?;
+        let set = fingerprints.get_or_insert_default();
+
+        let path = if set.contains(&fingerprint) {
+            None
+        } else {
+            let path = self.write_exchange_unique(exchange)?;
+            set.insert(fingerprint);
+
+            Some(path)
+        };
+
+        drop(fingerprints);
+
+        Ok(path)
+    }
+
+    /// The lock on the fingerprint set, which is built if necessary.
+    fn fingerprints(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, Option<std::collections::HashSet<u64>>>, Error> {
+        let mut fingerprints = self
+            .fingerprints
+            .0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        if fingerprints.is_none() {
+            let mut set = std::collections::HashSet::new();
+            let contents = self.contents(false)?;
+
+            for (_, contents) in contents {
+                // Files that cannot be read or parsed are skipped.
+                if let Some(fingerprint) = contents.ok().and_then(|contents| {
+                    serde_json::from_str::<Exchange<'_, serde::de::IgnoredAny>>(&contents)
+                        .ok()
+                        .map(|exchange| fingerprint(&exchange.request))
+                }) {
+                    set.insert(fingerprint);
+                }
+            }
+
+            *fingerprints = Some(set);
+        }
+
+        Ok(fingerprints)
+    }
+
+    /// Write the exchange to a new file, never overwriting an existing one.
+    ///
+    /// The file is named `{timestamp_ms}.json` if that name is free, and otherwise gets a suffix
+    /// from a counter shared by all clones of this store (e.g. `{timestamp_ms}-3.json`).
+    pub fn write_exchange_unique<T: serde::Serialize>(
+        &self,
+        exchange: &Exchange<'_, T>,
+    ) -> Result<PathBuf, Error> {
+        let timestamp_ms = exchange.request.timestamp.timestamp_millis();
+        let json = serde_json::to_string(exchange)?;
+        let mut path = self.base.join(format!("{timestamp_ms}.json"));
+
+        loop {
+            // This fails if the file already exists, and never leaves a partially written file.
+            match write_file_atomic(&path, &json, Format::Json(Compression::None), false) {
+                Ok(()) => return Ok(path),
+                Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let sequence = self.sequence.next();
+                    path = self.base.join(format!("{timestamp_ms}-{sequence}.json"));
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    /// Write an exchange along with a metadata sidecar file.
+    ///
+    /// The sidecar is written next to the exchange as `{timestamp_ms}.meta.json`, and is not
+    /// included in the store's paths.
+    pub fn write_exchange_with_meta<T: serde::Serialize, M: serde::Serialize>(
+        &self,
+        exchange: &Exchange<'_, T>,
+        meta: &M,
+    ) -> Result<(PathBuf, PathBuf), Error> {
+        let exchange_path = exchange.save_file(&self.base)?;
+        let meta_path = meta_path(&exchange_path);
+
+        std::fs::write(&meta_path, serde_json::to_string(meta)?)?;
+
+        Ok((exchange_path, meta_path))
+    }
+
+    /// Read the metadata sidecar for the exchange at the given path, if there is one.
+    pub fn read_meta<M: serde::de::DeserializeOwned>(
+        &self,
+        exchange_path: &Path,
+    ) -> Result<Option<M>, Error> {
+        match std::fs::read_to_string(meta_path(exchange_path)) {
+            Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Record the current archive format version in the store directory.
+    pub fn write_version(&self) -> Result<(), std::io::Error> {
+        std::fs::write(
+            self.base.join(VERSION_FILE_NAME),
+            FORMAT_VERSION.to_string(),
+        )
+    }
+
+    /// The archive format version recorded in the store directory, if there is one.
+    pub fn format_version(&self) -> Result<Option<u32>, Error> {
+        match std::fs::read_to_string(self.base.join(VERSION_FILE_NAME)) {
+            Ok(contents) => contents
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| Error::InvalidFormatVersion(contents)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Rewrite every exchange in the current archive format and record the format version.
+    ///
+    /// Files whose JSON is already equivalent to the current format are left untouched. Returns the
+    /// number of files rewritten.
+    pub fn migrate(&self) -> Result<usize, Error> {
+        let version = self.format_version()?;
+        let mut count = 0;
+
+        for path in self.paths(false)? {
+            let contents = read_file(&path)?;
+            let mut exchange = serde_json::from_str::<Exchange<'_, serde_json::Value>>(&contents)?;
+            apply_format_defaults(version, &mut exchange);
+
+            let migrated = serde_json::to_value(&exchange)?;
+
+            if migrated != serde_json::from_str::<serde_json::Value>(&contents)? {
+                write_file(
+                    &path,
+                    &serde_json::to_string(&migrated)?,
+                    Format::for_path(&path),
+                )?;
+                count += 1;
+            }
+        }
+
+        self.write_version()?;
+
+        Ok(count)
+    }
+
+    /// Compute a digest of the store's listing, for cheap change detection.
+    ///
+    /// The digest is a SHA-256 hash of the sorted file names, sizes, and modification times, so
+    /// no file contents are read. It is stable as long as no files are added, removed, or
+    /// modified.
+    pub fn digest(&self) -> Result<[u8; 32], Error> {
+        use sha2::Digest;
+
+        let mut hasher = sha2::Sha256::new();
+
+        for path in self.paths(false)? {
+            let metadata = std::fs::metadata(&path)?;
+            let modified = metadata
+                .modified()?
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default();
+
+            if let Some(file_name) = path.file_name() {
+                hasher.update(file_name.as_encoded_bytes());
+            }
+
+            // The separator keeps file names from running into the following fields.
+            hasher.update([0]);
+            hasher.update(metadata.len().to_be_bytes());
+            hasher.update(modified.as_nanos().to_be_bytes());
+        }
+
+        Ok(hasher.finalize().into())
+    }
+
+    /// Write a manifest of SHA-256 hashes of every exchange file, in `sha256sum` format.
+    pub fn write_manifest(&self) -> Result<PathBuf, Error> {
+        let manifest = self
+            .file_hashes()?
+            .iter()
+            .flat_map(|(file_name, hash)| [hash.as_str(), "  ", file_name.as_str(), "\n"])
+            .collect::<String>();
+
+        let path = self.base.join(MANIFEST_FILE_NAME);
+        std::fs::write(&path, manifest)?;
+
+        Ok(path)
+    }
+
+    /// Check the exchange files against the manifest written by [`Self::write_manifest`].
+    pub fn verify_manifest(&self) -> Result<VerifyReport, Error> {
+        let manifest = std::fs::read_to_string(self.base.join(MANIFEST_FILE_NAME))?;
+        let mut expected = std::collections::BTreeMap::new();
+
+        for line in manifest.lines().filter(|line| !line.is_empty()) {
+            let (hash, file_name) = line
+                .split_once("  ")
+                .ok_or_else(|| Error::InvalidManifestLine(line.to_string()))?;
+
+            expected.insert(file_name.to_string(), hash.to_string());
+        }
+
+        let mut report = VerifyReport::default();
+
+        for (file_name, hash) in self.file_hashes()? {
+            match expected.remove(&file_name) {
+                Some(expected_hash) if expected_hash == hash => {}
+                Some(_) => report.mismatched.push(file_name),
+                None => report.extra.push(file_name),
+            }
+        }
+
+        report.missing = expected.into_keys().collect();
+
+        Ok(report)
+    }
+
+    fn file_hashes(&self) -> Result<Vec<(String, String)>, Error> {
+        use sha2::Digest;
+
+        self.paths(false)?
+            .into_iter()
+            .filter(|path| path.is_file())
+            .map(|path| {
+                let hash = sha2::Sha256::digest(std::fs::read(&path)?);
+                let file_name = path
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+
+                Ok((file_name, crate::hex::encode(&hash)))
+            })
+            .collect()
+    }
+
+    /// Re-send every archived request in capture order, yielding the fresh exchanges.
+    ///
+    /// Each request is sent with a new timestamp, and if `delay_between` is provided, the stream
+    /// waits that long between requests. Files are read on Tokio's blocking thread pool.
+    #[cfg(feature = "tokio")]
+    pub fn replay<'c>(
+        &self,
+        client: &'c reqwest::Client,
+        delay_between: Option<std::time::Duration>,
+    ) -> impl futures::Stream<Item = Result<Exchange<'static, serde_json::Value>, Error>> + 'c {
+        let paths = match self.format_version().and_then(|version| {
+            Ok(self
+                .paths(false)?
+                .into_iter()
+                .map(|path| Ok((version, path)))
+                .collect::<Vec<_>>())
+        }) {
+            Ok(paths) => paths,
+            Err(error) => vec![Err(error)],
+        };
+
+        futures::stream::iter(paths.into_iter().enumerate()).then(move |(index, path)| async move {
+            if index > 0
+                && let Some(delay) = delay_between
+            {
+                tokio::time::sleep(delay).await;
+            }
+
+            let (version, path) = path?;
+            let mut request = tokio::task::spawn_blocking(move || {
+                parse_exchange::<serde::de::IgnoredAny>(version, read_file(&path))
+            })
+            .await
+            .unwrap_or_else(|error| std::panic::resume_unwind(error.into_panic()))?
+            .request;
+            request.timestamp = chrono::Utc::now();
+
+            Ok(crate::client::json_send(client, request)
+                .await?
+                .into_owned())
+        })
+    }
+
+    /// Rename files whose names do not match the timestamp of the request they contain.
+    ///
+    /// A name matches if it is the timestamp, optionally followed by a suffix after a hyphen
+    /// (as written by [`Self::write_exchange_unique`] or
+    /// [`crate::exchange::TimestampUrlHashNaming`]). If the expected file name is already taken,
+    /// a counter is appended to the timestamp (e.g. `1760252742866-1.json`). Every file is read
+    /// before any are renamed, so a file that cannot be read or parsed leaves the store
+    /// unchanged. Returns the number of files renamed.
+    pub fn reindex(&self) -> Result<usize, Error> {
+        let mut renames = vec![];
+
+        for path in self.paths(false)? {
+            let contents = read_file(&path)?;
+            let exchange = serde_json::from_str::<Exchange<'_, serde::de::IgnoredAny>>(&contents)?;
+            let timestamp_ms = exchange.request.timestamp.timestamp_millis().to_string();
+            let extension = Format::for_path(&path).extension();
+
+            let matches = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(|file_name| file_name.strip_suffix(extension)?.strip_suffix('.'))
+                .and_then(|stem| stem.strip_prefix(&timestamp_ms))
+                .is_some_and(|suffix| suffix.is_empty() || suffix.starts_with('-'));
+
+            if !matches {
+                renames.push((path, timestamp_ms, extension));
+            }
+        }
+
+        for (path, timestamp_ms, extension) in &renames {
+            let mut new_path = self.base.join(format!("{timestamp_ms}.{extension}"));
+            let mut counter = 0;
+
+            while new_path.exists() {
+                counter += 1;
+                new_path = self
+                    .base
+                    .join(format!("{timestamp_ms}-{counter}.{extension}"));
+            }
+
+            std::fs::rename(path, new_path)?;
+        }
+
+        Ok(renames.len())
+    }
+
+    /// Iterate over the exchanges whose response data satisfies the predicate.
+    ///
+    /// Files that cannot be read or parsed are always included (as errors).
+    pub fn filter_by_data<T: serde::de::DeserializeOwned + 'static, P: FnMut(&T) -> bool>(
+        &self,
+        reverse: bool,
+        mut predicate: P,
+    ) -> Result<
+        impl Iterator<Item = (PathBuf, Result<Exchange<'static, T>, Error>)> + use<T, P>,
+        Error,
+    > {
+        let version = self.format_version()?;
+
+        Ok(self
+            .contents(reverse)?
+            .map(move |(path, contents)| (path, parse_exchange(version, contents)))
+            .filter(move |(_, exchange)| {
+                exchange
+                    .as_ref()
+                    .map_or(true, |exchange| predicate(&exchange.response.data))
+            }))
+    }
+
+    /// Iterate over the exchanges whose recorded response status is not successful (2xx).
+    ///
+    /// Exchanges without a recorded status (from archives that predate status recording) are
+    /// assumed to be successful, and files that cannot be read or parsed are skipped.
+    pub fn errors<T: serde::de::DeserializeOwned + 'static>(
+        &self,
+        reverse: bool,
+    ) -> Result<impl Iterator<Item = (PathBuf, Exchange<'static, T>)> + use<T>, Error> {
+        let version = self.format_version()?;
+
+        Ok(self.contents(reverse)?.filter_map(move |(path, contents)| {
+            parse_exchange::<T>(version, contents)
+                .ok()
+                .filter(|exchange| {
+                    exchange
+                        .response
+                        .status
+                        .is_some_and(|status| !status.is_success())
+                })
+                .map(|exchange| (path, exchange))
+        }))
+    }
+
+    /// Iterate over the parsed exchanges, in capture order (or the reverse).
+    ///
+    /// See [`Self::contents`] for the unparsed file contents.
+    pub fn exchanges<T: serde::de::DeserializeOwned + 'static>(
+        &self,
+        reverse: bool,
+    ) -> Result<impl Iterator<Item = (PathBuf, Result<Exchange<'static, T>, Error>)> + use<T>, Error>
+    {
+        let version = self.format_version()?;
+
+        Ok(self
+            .contents(reverse)?
+            .map(move |(path, contents)| (path, parse_exchange(version, contents))))
+    }
+
+    /// Iterate over the exchanges carrying the given tag.
+    ///
+    /// Files that cannot be read or parsed are skipped.
+    pub fn with_tag<T: serde::de::DeserializeOwned + 'static>(
+        &self,
+        tag: &str,
+        reverse: bool,
+    ) -> Result<impl Iterator<Item = (PathBuf, Exchange<'static, T>)> + use<T>, Error> {
+        let version = self.format_version()?;
+        let tag = tag.to_string();
+
+        Ok(self.contents(reverse)?.filter_map(move |(path, contents)| {
+            parse_exchange::<T>(version, contents)
+                .ok()
+                .filter(|exchange| exchange.has_tag(&tag))
+                .map(|exchange| (path, exchange))
+        }))
+    }
+
+    /// Group the paths of the exchanges by a key computed from each exchange.
+    ///
+    /// Only the paths are kept, so the caller can load the exchanges in each group as needed.
+    /// Paths within each group are in capture order.
+    pub fn group_by<
+        T: serde::de::DeserializeOwned + 'static,
+        K: Eq + std::hash::Hash,
+        F: FnMut(&Exchange<'_, T>) -> K,
+    >(
+        &self,
+        mut key_fn: F,
+    ) -> Result<std::collections::HashMap<K, Vec<PathBuf>>, Error> {
+        let version = self.format_version()?;
+        let mut groups = std::collections::HashMap::<K, Vec<PathBuf>>::new();
+
+        for (path, contents) in self.contents(false)? {
+            let exchange = parse_exchange::<T>(version, contents)?;
+
+            groups.entry(key_fn(&exchange)).or_default().push(path);
+        }
+
+        Ok(groups)
+    }
+
+    /// Find the exchanges that duplicate an earlier one (or a later one, if `keep_latest` is set),
+    /// where duplicates have the same key.
+    ///
+    /// Nothing is deleted: the paths of the duplicates are returned in capture order, so that the
+    /// caller can decide what to do with them.
+    pub fn dedup<
+        T: serde::de::DeserializeOwned + 'static,
+        K: Eq + std::hash::Hash,
+        F: Fn(&Exchange<'_, T>) -> K,
+    >(
+        &self,
+        key_fn: F,
+        keep_latest: bool,
+    ) -> Result<Vec<PathBuf>, Error> {
+        let mut seen = std::collections::HashSet::new();
+        let mut duplicates = vec![];
+
+        for (path, exchange) in self.exchanges::<T>(keep_latest)? {
+            if !seen.insert(key_fn(&exchange?)) {
+                duplicates.push(path);
+            }
+        }
+
+        if keep_latest {
+            duplicates.reverse();
+        }
+
+        Ok(duplicates)
+    }
+
+    /// Read a random sample of up to `n` exchanges, in path order.
+    ///
+    /// The directory listing is sampled as it is read, so the full list of paths is never held
+    /// in memory.
+    pub fn sample<T: serde::de::DeserializeOwned + 'static>(
+        &self,
+        n: usize,
+        rng: &mut impl rand::Rng,
+    ) -> Result<Vec<Exchange<'static, T>>, Error> {
+        use rand::RngExt;
+
+        let version = self.format_version()?;
+        let mut reservoir = Vec::with_capacity(n);
+        let mut seen = 0;
+
+        for entry in std::fs::read_dir(&self.base)? {
+            let path = entry?.path();
+
+            if !is_exchange_path(&path) || path.is_dir() {
+                continue;
+            }
+
+            if reservoir.len() < n {
+                reservoir.push(path);
+            } else {
+                let index = rng.random_range(0..=seen);
+
+                if index < n {
+                    reservoir[index] = path;
+                }
+            }
+
+            seen += 1;
+        }
+
+        reservoir.sort();
+
+        reservoir
+            .into_iter()
+            .map(|path| parse_exchange(version, read_file(&path)))
+            .collect()
+    }
+
+    /// Read and parse every exchange in parallel, in no particular order.
+    #[cfg(feature = "rayon")]
+    pub fn par_exchanges<T: serde::de::DeserializeOwned + Send + 'static>(
+        &self,
+    ) -> Result<
+        impl rayon::iter::ParallelIterator<Item = (PathBuf, Result<Exchange<'static, T>, Error>)>
+        + use<T>,
+        Error,
+    > {
+        use rayon::prelude::*;
+
+        let version = self.format_version()?;
+
+        Ok(self.paths(false)?.into_par_iter().map(move |path| {
+            let exchange = parse_exchange(version, read_file(&path));
+
+            (path, exchange)
+        }))
+    }
+
+    /// Map every exchange in parallel and combine the results.
+    ///
+    /// The reducer should be associative, and `init` should be an identity for it, since it may
+    /// be used any number of times. Fails with one of the errors encountered reading or parsing
+    /// files (not necessarily the first in path order).
+    #[cfg(feature = "rayon")]
+    pub fn par_fold<T, A, M, R>(&self, map: M, reduce: R, init: A) -> Result<A, Error>
+    where
+        T: serde::de::DeserializeOwned + 'static,
+        A: Clone + Send + Sync,
+        M: Fn(Exchange<'static, T>) -> A + Sync,
+        R: Fn(A, A) -> A + Sync,
+    {
+        use rayon::prelude::*;
+
+        let version = self.format_version()?;
+
+        self.paths(false)?
+            .par_iter()
+            .map(|path| parse_exchange(version, read_file(path)).map(&map))
+            .try_reduce(|| init.clone(), |left, right| Ok(reduce(left, right)))
+    }
+
     pub fn entries<T>(&self, reverse: bool) -> Result<Entries<T>, std::io::Error> {
         Ok(Entries {
             contents: self.contents(reverse)?,
@@ -54,6 +886,206 @@ impl Store {
     }
 }
 
+/// The current version of the on-disk archive format.
+///
+/// Version 2 added the response status. Earlier archives only contain successful responses.
+pub const FORMAT_VERSION: u32 = 2;
+
+const VERSION_FILE_NAME: &str = ".scraper-trail-version";
+const MANIFEST_FILE_NAME: &str = "manifest.sha256";
+
+/// The result of checking a store against its manifest.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VerifyReport {
+    /// Files listed in the manifest that are not in the store.
+    pub missing: Vec<String>,
+    /// Files in the store that are not listed in the manifest.
+    pub extra: Vec<String>,
+    /// Files whose contents do not match their hashes.
+    pub mismatched: Vec<String>,
+}
+
+impl VerifyReport {
+    #[must_use]
+    pub const fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+/// Fill in fields that archives written before the given format version cannot contain.
+fn apply_format_defaults<T>(version: Option<u32>, exchange: &mut Exchange<'_, T>) {
+    if version.is_none_or(|version| version < 2) && exchange.response.status.is_none() {
+        exchange.response.status = Some(http::StatusCode::OK);
+    }
+}
+
+pub(super) fn parse_exchange<T: serde::de::DeserializeOwned + 'static>(
+    version: Option<u32>,
+    contents: Result<String, std::io::Error>,
+) -> Result<Exchange<'static, T>, Error> {
+    let contents = contents?;
+    let mut exchange = serde_json::from_str::<Exchange<'_, T>>(&contents)?.into_owned();
+    apply_format_defaults(version, &mut exchange);
+
+    Ok(exchange)
+}
+
+/// Write exchanges as newline-delimited JSON (see [`Exchange::to_ndjson_line`]), returning the
+/// number written.
+pub fn write_ndjson<
+    'a,
+    T: serde::Serialize + 'a,
+    W: std::io::Write,
+    I: IntoIterator<Item = Exchange<'a, T>>,
+>(
+    mut writer: W,
+    exchanges: I,
+) -> Result<usize, Error> {
+    let mut count = 0;
+
+    for exchange in exchanges {
+        writer.write_all(crate::canonical::to_string(&exchange)?.as_bytes())?;
+        writer.write_all(b"\n")?;
+        count += 1;
+    }
+
+    writer.flush()?;
+
+    Ok(count)
+}
+
+/// Read exchanges from newline-delimited JSON, skipping blank lines.
+///
+/// A line that cannot be parsed results in an error item, but reading continues with the next
+/// line. Reading stops after an I/O error.
+pub fn read_ndjson<T: serde::de::DeserializeOwned + 'static, R: std::io::BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<Exchange<'static, T>, Error>> {
+    let mut failed = false;
+
+    reader
+        .lines()
+        .map_while(move |line| {
+            if failed {
+                return None;
+            }
+
+            Some(match line {
+                Ok(line) if line.trim().is_empty() => None,
+                Ok(line) => Some(
+                    serde_json::from_str::<Exchange<'_, T>>(&line)
+                        .map(Exchange::into_owned)
+                        .map_err(Error::from),
+                ),
+                Err(error) => {
+                    failed = true;
+                    Some(Err(error.into()))
+                }
+            })
+        })
+        .flatten()
+}
+
+/// A counter for file name suffixes, shared between clones of a store.
+#[derive(Clone, Debug, Default)]
+struct Sequence(std::sync::Arc<std::sync::atomic::AtomicU64>);
+
+impl Sequence {
+    fn next(&self) -> u64 {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1
+    }
+}
+
+// The counter is an implementation detail that does not affect which store is referred to.
+impl PartialEq for Sequence {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Sequence {}
+
+/// Hashes of the cache keys of the requests in a store, shared between clones of a store.
+#[derive(Clone, Debug, Default)]
+struct Fingerprints(std::sync::Arc<std::sync::Mutex<Option<std::collections::HashSet<u64>>>>);
+
+// The cache is an implementation detail that does not affect which store is referred to.
+impl PartialEq for Fingerprints {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Fingerprints {}
+
+fn fingerprint(request: &crate::request::Request<'_>) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::hash::DefaultHasher::new();
+    request.cache_key().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The 1-based nearest rank for a percentile (clamped to the valid range) of `len` sorted values.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn nearest_rank(percentile: f64, len: usize) -> usize {
+    ((percentile / 100.0 * len as f64).ceil() as usize).clamp(1, len)
+}
+
+/// The timestamp and counter from a file name like `{timestamp_ms}-{counter}.json`.
+fn numeric_key(path: &Path) -> Option<(u64, u64)> {
+    let file_name = path.file_name()?.to_str()?;
+    let (stem, _) = file_name.split_once('.')?;
+    let (timestamp, counter) = stem.split_once('-').unwrap_or((stem, "0"));
+
+    Some((timestamp.parse().ok()?, counter.parse().ok()?))
+}
+
+/// Order paths by directory, and then by the timestamp and counter in the file name (so that
+/// `1-2.json` comes after `1.json` and before `1-10.json`), falling back to the file name.
+fn compare_paths(a: &Path, b: &Path) -> std::cmp::Ordering {
+    a.parent()
+        .cmp(&b.parent())
+        .then_with(|| match (numeric_key(a), numeric_key(b)) {
+            (Some(key_a), Some(key_b)) => key_a.cmp(&key_b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        })
+        .then_with(|| a.file_name().cmp(&b.file_name()))
+}
+
+const META_EXTENSION: &str = "meta.json";
+
+fn meta_path(exchange_path: &Path) -> PathBuf {
+    let path = if let Format::Json(Compression::Gzip { .. }) = Format::for_path(exchange_path) {
+        // Remove the `.gz` so that the `.json` is replaced below.
+        exchange_path.with_extension("")
+    } else {
+        exchange_path.to_path_buf()
+    };
+
+    path.with_extension(META_EXTENSION)
+}
+
+fn is_exchange_path(path: &Path) -> bool {
+    !is_meta_path(path)
+        && !path.ends_with(VERSION_FILE_NAME)
+        && !path.ends_with(MANIFEST_FILE_NAME)
+        // Temporary files may be left by interrupted writes.
+        && path.extension().is_none_or(|extension| extension != TEMP_EXTENSION)
+}
+
+fn is_meta_path(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|file_name| file_name.to_str())
+        .is_some_and(|file_name| file_name.ends_with(&format!(".{META_EXTENSION}")))
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Contents {
     paths: Vec<PathBuf>,
@@ -64,7 +1096,7 @@ impl Iterator for Contents {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.paths.pop().map(|path| {
-            let contents = std::fs::read_to_string(&path);
+            let contents = read_file(&path);
 
             (path, contents)
         })
@@ -77,8 +1109,6 @@ pub struct Entries<T> {
     _target: PhantomData<T>,
 }
 
-use bounded_static::IntoBoundedStatic;
-
 impl<T: Archiveable + IntoBoundedStatic> Iterator for Entries<T>
 where
     T::Static: Archiveable,
@@ -101,3 +1131,870 @@ where
         })
     }
 }
+
+/// Helpers for tests that need a store on disk.
+#[cfg(test)]
+pub(super) mod testing {
+    use super::Store;
+    use crate::exchange::Exchange;
+    use std::path::PathBuf;
+
+    const APPLE_ITUNES_01_EXAMPLE: &str = include_str!("../../../examples/apple-itunes-01.json");
+
+    /// The example exchange that test stores are filled with.
+    pub fn example() -> Result<Exchange<'static, serde_json::Value>, serde_json::Error> {
+        serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)
+    }
+
+    /// Create an empty store in the temporary directory, removing anything left by earlier runs.
+    pub fn test_store(name: &str) -> Result<TestStore, std::io::Error> {
+        let base =
+            std::env::temp_dir().join(format!("scraper-trail-test-{name}-{}", std::process::id()));
+
+        if base.exists() {
+            std::fs::remove_dir_all(&base)?;
+        }
+
+        std::fs::create_dir_all(&base)?;
+
+        Ok(TestStore(Store::new(base)))
+    }
+
+    /// A store whose directory is removed when it is dropped, even if the test fails.
+    pub struct TestStore(Store);
+
+    impl TestStore {
+        /// Save a copy of the example exchange for each item, after updating it with the item.
+        ///
+        /// The copies are a second apart, starting at the example's timestamp.
+        pub fn save_examples<
+            I: IntoIterator,
+            F: FnMut(&mut Exchange<'static, serde_json::Value>, I::Item),
+        >(
+            &self,
+            items: I,
+            mut update: F,
+        ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+            let mut exchange = example()?;
+            let mut paths = vec![];
+
+            for item in items {
+                update(&mut exchange, item);
+                paths.push(exchange.save_file(&self.base)?);
+                exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+            }
+
+            Ok(paths)
+        }
+    }
+
+    impl std::ops::Deref for TestStore {
+        type Target = Store;
+
+        fn deref(&self) -> &Store {
+            &self.0
+        }
+    }
+
+    impl Drop for TestStore {
+        fn drop(&mut self) {
+            // The directory may already have been removed.
+            let _ = std::fs::remove_dir_all(&self.0.base);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{example, test_store};
+    use crate::exchange::Exchange;
+
+    #[test]
+    fn write_and_read_meta() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("meta")?;
+        let exchange = example()?;
+
+        let (exchange_path, meta_path) =
+            store.write_exchange_with_meta(&exchange, &serde_json::json!({"note": "test"}))?;
+
+        assert_eq!(meta_path, store.base.join("1760252742866.meta.json"));
+        assert_eq!(store.paths(false)?, vec![exchange_path.clone()]);
+        assert_eq!(
+            store.read_meta::<serde_json::Value>(&exchange_path)?,
+            Some(serde_json::json!({"note": "test"}))
+        );
+        assert_eq!(
+            store.read_meta::<serde_json::Value>(&store.base.join("0.json"))?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn paths_recursive_symlinks() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("symlinks")?;
+        let outside = test_store("symlinks-outside")?;
+        let nested = store.base.join("nested");
+
+        std::fs::create_dir(&nested)?;
+        std::fs::write(store.base.join("1.json"), "{}")?;
+        std::fs::write(nested.join("2.json"), "{}")?;
+        std::fs::write(outside.base.join("3.json"), "{}")?;
+        std::os::unix::fs::symlink(&store.base, nested.join("cycle"))?;
+        std::os::unix::fs::symlink(outside.base.join("3.json"), store.base.join("3.json"))?;
+
+        let expected = vec![store.base.join("1.json"), nested.join("2.json")];
+
+        assert_eq!(store.paths_recursive(false, false)?, expected);
+        assert_eq!(store.paths_recursive(false, true)?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_if_new() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("write-if-new")?;
+        let mut exchange = example()?;
+
+        let existing = exchange.save_file(&store.base)?;
+        // Unreadable files are skipped.
+        std::fs::write(store.base.join("1.json"), "{")?;
+
+        assert!(store.contains_request(&exchange.request)?);
+        assert!(store.write_if_new(&exchange)?.is_none());
+
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        exchange.request.url.set_query(Some("id=1"));
+
+        // Clones share the fingerprints.
+        let shared = store.clone();
+        let new = shared.write_if_new(&exchange)?;
+
+        assert!(new.is_some());
+        assert!(store.contains_request(&exchange.request)?);
+
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+
+        assert!(store.write_if_new(&exchange)?.is_none());
+        assert_eq!(
+            store.paths(false)?,
+            vec![
+                store.base.join("1.json"),
+                existing,
+                new.ok_or("missing path")?
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_if_new_same_millisecond() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("write-if-new-same-millisecond")?;
+        let mut exchange = example()?;
+
+        let first = store.write_if_new(&exchange)?.ok_or("missing path")?;
+
+        // A different request captured in the same millisecond does not replace the first file.
+        exchange.request.url.set_query(Some("id=1"));
+        let second = store.write_if_new(&exchange)?.ok_or("missing path")?;
+
+        assert_ne!(first, second);
+        assert_eq!(store.paths(false)?, vec![first, second]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_if_new_failure() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("write-if-new-failure")?;
+        let exchange = example()?;
+
+        assert!(!store.contains_request(&exchange.request)?);
+
+        // The fingerprints have been built, so removing the directory makes the write fail.
+        std::fs::remove_dir_all(&store.base)?;
+
+        assert!(store.write_if_new(&exchange).is_err());
+        assert!(!store.contains_request(&exchange.request)?);
+
+        std::fs::create_dir_all(&store.base)?;
+
+        assert!(store.write_if_new(&exchange)?.is_some());
+        assert!(store.contains_request(&exchange.request)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_adds_status() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("migrate")?;
+        let exchange = example()?;
+        let path = exchange.save_file(&store.base)?;
+
+        assert_eq!(store.format_version()?, None);
+        assert_eq!(store.migrate()?, 1);
+        assert_eq!(store.format_version()?, Some(super::FORMAT_VERSION));
+        assert_eq!(store.migrate()?, 0);
+
+        let contents = std::fs::read_to_string(path)?;
+        let migrated: Exchange<'_, serde_json::Value> = serde_json::from_str(&contents)?;
+
+        assert_eq!(migrated.response.status, Some(http::StatusCode::OK));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_fold() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("par-fold")?;
+        store.save_examples(0..5, |_, _| {})?;
+
+        let count = store.par_fold(
+            |_: Exchange<'static, serde_json::Value>| 1,
+            |left, right| left + right,
+            0,
+        )?;
+
+        assert_eq!(count, 5);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_exchanges() -> Result<(), Box<dyn std::error::Error>> {
+        use rayon::prelude::*;
+
+        let store = test_store("par-exchanges")?;
+        store.save_examples(0..5, |_, _| {})?;
+
+        let mut results = store
+            .par_exchanges::<serde_json::Value>()?
+            .map(|(path, exchange)| exchange.map(|exchange| (path, exchange.request.timestamp)))
+            .collect::<Result<Vec<_>, _>>()?;
+        results.sort();
+
+        assert_eq!(
+            results
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>(),
+            store.paths(false)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn manifest() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("manifest")?;
+        let mut exchange = example()?;
+
+        let first = exchange.save_file(&store.base)?;
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        let second = exchange.save_file(&store.base)?;
+
+        store.write_manifest()?;
+
+        assert_eq!(store.paths(false)?.len(), 2);
+        assert!(store.verify_manifest()?.is_ok());
+
+        std::fs::write(&first, "{}")?;
+        std::fs::remove_file(&second)?;
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        let third = exchange.save_file(&store.base)?;
+
+        let file_name = |path: &std::path::Path| {
+            path.file_name()
+                .map(|file_name| file_name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        let report = store.verify_manifest()?;
+
+        assert_eq!(report.mismatched, vec![file_name(&first)]);
+        assert_eq!(report.missing, vec![file_name(&second)]);
+        assert_eq!(report.extra, vec![file_name(&third)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_tag() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("with-tag")?;
+        let exchange = example()?;
+
+        exchange.save_file(&store.base)?;
+
+        let mut exchange = exchange.with_tags(["reviews", "us"]);
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        let tagged_path = exchange.save_file(&store.base)?;
+
+        let tagged = store
+            .with_tag::<serde_json::Value>("us", false)?
+            .collect::<Vec<_>>();
+
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].0, tagged_path);
+        assert_eq!(tagged[0].1.tags, vec!["reviews", "us"]);
+        assert_eq!(store.with_tag::<serde_json::Value>("uk", false)?.count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sample() -> Result<(), Box<dyn std::error::Error>> {
+        use rand::SeedableRng;
+
+        let store = test_store("sample")?;
+        store.save_examples(0..10, |_, _| {})?;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let sample = store.sample::<serde_json::Value>(3, &mut rng)?;
+
+        assert_eq!(sample.len(), 3);
+        assert!(
+            sample
+                .windows(2)
+                .all(|pair| pair[0].request.timestamp < pair[1].request.timestamp)
+        );
+        assert_eq!(store.sample::<serde_json::Value>(20, &mut rng)?.len(), 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn gzipped_files() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("gzipped-files")?;
+        let mut exchange = example()?;
+
+        let first = exchange.save_file(&store.base)?;
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        let second = exchange.save_file_gz(&store.base)?;
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        let third = exchange.save_file(&store.base)?;
+
+        assert!(second.to_string_lossy().ends_with(".json.gz"));
+        assert_eq!(store.paths(false)?, vec![first, second.clone(), third]);
+        assert_eq!(super::meta_path(&second).extension(), Some("json".as_ref()));
+
+        let timestamps = store
+            .contents(false)?
+            .map(|(_, contents)| {
+                let contents = contents?;
+                let exchange: Exchange<'_, serde_json::Value> = serde_json::from_str(&contents)?;
+                Ok(exchange.request.timestamp)
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+        assert_eq!(timestamps.len(), 3);
+        assert!(timestamps.is_sorted());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn binary_files() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("binary-files")?;
+        let mut exchange = example()?;
+
+        let first = exchange.save_file_bin(&store.base)?;
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        let second = exchange.save_file(&store.base)?;
+
+        assert!(first.to_string_lossy().ends_with(".bin"));
+        assert_eq!(store.paths(false)?, vec![first.clone(), second]);
+        assert_eq!(
+            super::meta_path(&first).file_name(),
+            first.with_extension("meta.json").file_name()
+        );
+
+        let exchanges = store
+            .contents(false)?
+            .map(|(_, contents)| {
+                let contents = contents?;
+                let exchange: Exchange<'_, serde_json::Value> = serde_json::from_str(&contents)?;
+                Ok(exchange.into_owned())
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+        assert_eq!(exchanges.len(), 2);
+        assert_eq!(exchanges[1], exchange);
+        assert_eq!(
+            Exchange::<serde_json::Value>::from_bytes(&std::fs::read(&first)?)?
+                .request
+                .url,
+            exchange.request.url
+        );
+
+        // Migrated binary files are still binary.
+        store.migrate()?;
+
+        assert!(Exchange::<serde_json::Value>::from_bytes(&std::fs::read(&first)?).is_ok());
+        assert_eq!(store.migrate()?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn latency_percentiles() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("latency-percentiles")?;
+
+        assert_eq!(store.latency_percentiles(&[50.0])?, vec![]);
+
+        store.save_examples(
+            (1..=100).rev().map(Some).chain([None]),
+            |exchange, duration_ms| {
+                exchange.duration_ms = duration_ms;
+            },
+        )?;
+
+        assert_eq!(
+            store.latency_percentiles(&[50.0, 95.0, 99.0, 0.0, 100.0, 150.0])?,
+            vec![
+                (50.0, 50),
+                (95.0, 95),
+                (99.0, 99),
+                (0.0, 1),
+                (100.0, 100),
+                (150.0, 100)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn exchanges() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("exchanges")?;
+        let mut exchange = example()?;
+
+        let first = exchange.save_file(&store.base)?;
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        let second = exchange.save_file(&store.base)?;
+        let invalid = store.base.join(format!(
+            "{}.json",
+            exchange.request.timestamp.timestamp_millis() + 1000
+        ));
+        std::fs::write(&invalid, "{")?;
+
+        let results = store
+            .exchanges::<serde_json::Value>(true)?
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            results.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            vec![&invalid, &second, &first]
+        );
+        assert!(matches!(results[0].1, Err(super::Error::Json(_))));
+        assert_eq!(
+            results[1]
+                .1
+                .as_ref()
+                .ok()
+                .map(|exchange| exchange.request.timestamp),
+            Some(exchange.request.timestamp)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn latest_and_earliest() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("latest-and-earliest")?;
+
+        assert!(store.latest::<serde_json::Value>()?.is_none());
+
+        let start = example()?.request.timestamp;
+        let paths = store.save_examples(0..3, |_, _| {})?;
+
+        std::fs::write(store.base.join("notes.json"), "{")?;
+
+        let (latest_path, latest) = store
+            .latest::<serde_json::Value>()?
+            .ok_or("missing latest")?;
+        let (earliest_path, earliest) = store
+            .earliest::<serde_json::Value>()?
+            .ok_or("missing earliest")?;
+
+        assert_eq!(latest_path, paths[2]);
+        assert_eq!(
+            latest.request.timestamp,
+            start + chrono::TimeDelta::seconds(2)
+        );
+        assert_eq!(earliest_path, paths[0]);
+        assert_eq!(earliest.request.timestamp, start);
+
+        Ok(())
+    }
+
+    #[test]
+    fn count_and_is_empty() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("count")?;
+
+        assert_eq!(store.count()?, 0);
+        assert!(store.is_empty()?);
+
+        store.save_examples(0..2, |_, _| {})?;
+
+        let mut exchange = example()?;
+        exchange.request.timestamp += chrono::TimeDelta::seconds(2);
+
+        // Unparseable files are counted, but metadata files are not.
+        store.write_exchange_with_meta(&exchange, &serde_json::json!({}))?;
+        std::fs::write(store.base.join("notes.json"), "{")?;
+
+        assert_eq!(store.count()?, 4);
+        assert_eq!(store.count()?, store.paths(false)?.len());
+        assert!(!store.is_empty()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn digest() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("digest")?;
+        let mut exchange = example()?;
+        let empty = store.digest()?;
+
+        let path = exchange.save_file(&store.base)?;
+        let one = store.digest()?;
+
+        assert_ne!(one, empty);
+        assert_eq!(store.digest()?, one);
+
+        // Metadata files are not part of the listing.
+        std::fs::write(store.base.join("1.meta.json"), "{}")?;
+        assert_eq!(store.digest()?, one);
+
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        exchange.save_file(&store.base)?;
+        assert_ne!(store.digest()?, one);
+
+        std::fs::remove_file(&store.paths(true)?[0])?;
+        assert_eq!(store.digest()?, one);
+
+        // Changing a file's size changes the digest.
+        std::fs::write(&path, "{}")?;
+        assert_ne!(store.digest()?, one);
+
+        Ok(())
+    }
+
+    #[test]
+    fn filter_by_data() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("filter-by-data")?;
+        let paths = store.save_examples([0, 2, 5], |exchange, count| {
+            exchange.response.data = serde_json::json!({ "resultCount": count });
+        })?;
+
+        std::fs::write(store.base.join("invalid.json"), "{")?;
+
+        let results = store
+            .filter_by_data::<serde_json::Value, _>(true, |data| data["resultCount"] != 0)?
+            .collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 3);
+        // Files that cannot be parsed are always included (non-numeric names sort last).
+        assert!(matches!(results[0].1, Err(super::Error::Json(_))));
+        assert_eq!(results[1].0, paths[2]);
+        assert_eq!(results[2].0, paths[1]);
+        assert_eq!(
+            results[2]
+                .1
+                .as_ref()
+                .ok()
+                .map(|exchange| &exchange.response.data),
+            Some(&serde_json::json!({ "resultCount": 2 }))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("errors")?;
+        let paths = store.save_examples(
+            [
+                None,
+                Some(http::StatusCode::OK),
+                Some(http::StatusCode::NOT_FOUND),
+                Some(http::StatusCode::CREATED),
+                Some(http::StatusCode::SERVICE_UNAVAILABLE),
+            ],
+            |exchange, status| exchange.response.status = status,
+        )?;
+
+        std::fs::write(store.base.join("invalid.json"), "{")?;
+
+        let errors = store
+            .errors::<serde_json::Value>(false)?
+            .map(|(path, exchange)| (path, exchange.response.status))
+            .collect::<Vec<_>>();
+
+        // Unparseable files are skipped, and exchanges without a status are assumed successful.
+        assert_eq!(
+            errors,
+            vec![
+                (paths[2].clone(), Some(http::StatusCode::NOT_FOUND)),
+                (
+                    paths[4].clone(),
+                    Some(http::StatusCode::SERVICE_UNAVAILABLE)
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn paths_in_range() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("paths-in-range")?;
+        let start = example()?.request.timestamp;
+        let paths = store.save_examples(0..4, |_, _| {})?;
+
+        std::fs::write(store.base.join("notes.json"), "{}")?;
+
+        let second = start + chrono::TimeDelta::seconds(1);
+        let third = start + chrono::TimeDelta::seconds(2);
+
+        assert_eq!(
+            store.paths_in_range(Some(second), Some(third), false)?,
+            vec![paths[1].clone()]
+        );
+        assert_eq!(
+            store.paths_in_range(Some(second), None, true)?,
+            vec![paths[3].clone(), paths[2].clone(), paths[1].clone()]
+        );
+        assert_eq!(store.paths_in_range(None, None, false)?, paths);
+        assert!(store.paths_in_range(None, Some(start), false)?.is_empty());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn replay() -> Result<(), Box<dyn std::error::Error>> {
+        use futures::StreamExt;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"a": 1})))
+            .mount(&server)
+            .await;
+
+        let store = test_store("replay")?;
+        let archived_at = example()?.request.timestamp;
+        let urls = ["a", "b"]
+            .iter()
+            .map(|query| format!("{}/api?q={query}", server.uri()).parse())
+            .collect::<Result<Vec<url::Url>, _>>()?;
+
+        store.save_examples(urls, |exchange, url| exchange.request.url = url)?;
+
+        // Unreadable files are reported without stopping the replay.
+        std::fs::write(store.base.join("invalid.json"), "{")?;
+
+        let results = store
+            .replay(
+                &reqwest::Client::new(),
+                Some(std::time::Duration::from_millis(10)),
+            )
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(results.len(), 3);
+
+        for (result, query) in results[..2].iter().zip(["q=a", "q=b"]) {
+            let exchange = result.as_ref().map_err(ToString::to_string)?;
+
+            assert_eq!(exchange.request.url.query(), Some(query));
+            assert!(exchange.request.timestamp > archived_at);
+            assert_eq!(exchange.response.data, serde_json::json!({"a": 1}));
+        }
+
+        assert!(matches!(results[2], Err(super::Error::Json(_))));
+
+        std::fs::write(store.base.join(super::VERSION_FILE_NAME), "x")?;
+
+        let results = store
+            .replay(&reqwest::Client::new(), None)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert!(matches!(
+            results.as_slice(),
+            [Err(super::Error::InvalidFormatVersion(_))]
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn reindex() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("reindex")?;
+        let mut exchange = example()?;
+        let timestamp_ms = exchange.request.timestamp.timestamp_millis();
+
+        let correct = exchange.save_file(&store.base)?;
+        // A second exchange with the same timestamp, under the wrong name.
+        std::fs::write(
+            store.base.join("copy.json"),
+            serde_json::to_string(&exchange)?,
+        )?;
+
+        // Names with a suffix after the timestamp are left alone.
+        let unique = store.write_exchange_unique(&exchange)?;
+        let hashed = exchange.save_file_named(
+            &store.base,
+            crate::exchange::Compression::None,
+            &crate::exchange::TimestampUrlHashNaming,
+            false,
+        )?;
+
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        std::fs::write(store.base.join("1.json"), serde_json::to_string(&exchange)?)?;
+
+        assert_eq!(store.reindex()?, 2);
+
+        let mut expected = vec![
+            correct,
+            unique,
+            hashed,
+            store.base.join(format!("{timestamp_ms}-2.json")),
+            store.base.join(format!("{}.json", timestamp_ms + 1000)),
+        ];
+        expected.sort();
+        let mut paths = store.paths(false)?;
+        paths.sort();
+
+        assert_eq!(paths, expected);
+
+        // Reindexing again changes nothing.
+        assert_eq!(store.reindex()?, 0);
+
+        // A file that cannot be parsed stops the pass before anything is renamed.
+        std::fs::write(store.base.join("2.json"), serde_json::to_string(&exchange)?)?;
+        std::fs::write(store.base.join("3.json"), "{")?;
+
+        assert!(store.reindex().is_err());
+        assert!(store.base.join("2.json").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn dedup() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("dedup")?;
+        let paths = store.save_examples(["a", "b", "a", "a", "b"], |exchange, query| {
+            exchange.request.url.set_query(Some(query));
+        })?;
+
+        let key = |exchange: &Exchange<'_, serde::de::IgnoredAny>| exchange.request.cache_key();
+
+        assert_eq!(
+            store.dedup(key, false)?,
+            vec![paths[2].clone(), paths[3].clone(), paths[4].clone()]
+        );
+        assert_eq!(
+            store.dedup(key, true)?,
+            vec![paths[0].clone(), paths[1].clone(), paths[2].clone()]
+        );
+        assert_eq!(store.paths(false)?.len(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ndjson() -> Result<(), Box<dyn std::error::Error>> {
+        let mut exchange = example()?;
+        let first = exchange.clone();
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+
+        let mut output = vec![];
+
+        assert_eq!(
+            super::write_ndjson(&mut output, [first.clone(), exchange.clone()])?,
+            2
+        );
+
+        let mut input = String::from_utf8(output)?;
+        input.insert_str(0, "\n  \n");
+        input.push_str("{\"request\": 1}\n\n");
+        input.push_str(&first.to_ndjson_line()?);
+
+        let results =
+            super::read_ndjson::<serde_json::Value, _>(input.as_bytes()).collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().ok(), Some(&first));
+        assert_eq!(results[1].as_ref().ok(), Some(&exchange));
+        assert!(matches!(results[2], Err(super::Error::Json(_))));
+        assert_eq!(results[3].as_ref().ok(), Some(&first));
+
+        Ok(())
+    }
+
+    #[test]
+    fn group_by() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("group-by")?;
+        let urls = ["a.example.com", "b.example.com", "a.example.com"]
+            .iter()
+            .map(|host| {
+                let mut url = example()?.request.url;
+                url.set_host(Some(host))?;
+
+                Ok(url)
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+        let paths = store.save_examples(urls, |exchange, url| exchange.request.url = url)?;
+
+        let groups = store.group_by::<serde::de::IgnoredAny, _, _>(|exchange| {
+            exchange.request.url.host_str().map(str::to_string)
+        })?;
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups.get(&Some("a.example.com".to_string())),
+            Some(&vec![paths[0].clone(), paths[2].clone()])
+        );
+        assert_eq!(
+            groups.get(&Some("b.example.com".to_string())),
+            Some(&vec![paths[1].clone()])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_exchange_unique() -> Result<(), Box<dyn std::error::Error>> {
+        let store = test_store("write-exchange-unique")?;
+        let exchange = example()?;
+
+        let paths = (0..11)
+            .map(|_| store.clone().write_exchange_unique(&exchange))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(store.paths(false)?, paths);
+        // No temporary files are left behind.
+        assert_eq!(std::fs::read_dir(&store.base)?.count(), paths.len());
+
+        let timestamp_ms = exchange.request.timestamp.timestamp_millis();
+
+        assert_eq!(paths[0], store.base.join(format!("{timestamp_ms}.json")));
+        assert_eq!(
+            paths[10],
+            store.base.join(format!("{timestamp_ms}-10.json"))
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use super::store::{Error, Store};
+use std::io::{Read, Write};
+
+impl Store {
+    /// Write every exchange file into a tar stream, keeping its file name and modification time.
+    ///
+    /// Returns the number of files written.
+    pub fn export_tar<W: Write>(&self, out: W) -> Result<usize, Error> {
+        let mut builder = tar::Builder::new(out);
+        let mut count = 0;
+
+        for path in self.paths(false)? {
+            if let Some(file_name) = path.file_name()
+                && path.is_file()
+            {
+                builder.append_path_with_name(&path, file_name)?;
+                count += 1;
+            }
+        }
+
+        builder.into_inner()?.flush()?;
+
+        Ok(count)
+    }
+
+    /// Unpack the files in a tar stream (e.g. one written by [`Self::export_tar`]) into the store.
+    ///
+    /// Existing files with the same names are overwritten, and entries that would be written
+    /// outside the store directory are skipped. Returns the number of files unpacked.
+    pub fn import_tar<R: Read>(&self, input: R) -> Result<usize, Error> {
+        std::fs::create_dir_all(&self.base)?;
+
+        let mut archive = tar::Archive::new(input);
+        let mut count = 0;
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+
+            if entry.header().entry_type().is_file() && entry.unpack_in(&self.base)? {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::archive::store::testing::{example, test_store};
+
+    #[test]
+    fn export_and_import_tar() -> Result<(), Box<dyn std::error::Error>> {
+        let source = test_store("export-tar")?;
+        let mut exchange = example()?;
+
+        exchange.save_file(&source.base)?;
+        exchange.request.timestamp += chrono::TimeDelta::seconds(1);
+        exchange.save_file_gz(&source.base)?;
+
+        let mut tar = vec![];
+
+        assert_eq!(source.export_tar(&mut tar)?, 2);
+
+        let target = test_store("import-tar")?;
+
+        assert_eq!(target.import_tar(tar.as_slice())?, 2);
+
+        let source_paths = source.paths(false)?;
+        let target_paths = target.paths(false)?;
+
+        assert_eq!(
+            source_paths
+                .iter()
+                .map(|path| path.file_name())
+                .collect::<Vec<_>>(),
+            target_paths
+                .iter()
+                .map(|path| path.file_name())
+                .collect::<Vec<_>>()
+        );
+
+        for (source_path, target_path) in source_paths.iter().zip(&target_paths) {
+            assert_eq!(std::fs::read(source_path)?, std::fs::read(target_path)?);
+
+            // Tar stores modification times in whole seconds.
+            assert_eq!(
+                std::fs::metadata(source_path)?
+                    .modified()?
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_secs(),
+                std::fs::metadata(target_path)?
+                    .modified()?
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_secs()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+/// Recursively sort the keys of every object in the value.
+pub fn sort_keys(value: &mut serde_json::Value) {
+    let mut stack = vec![value];
+
+    while let Some(value) = stack.pop() {
+        match value {
+            serde_json::Value::Array(values) => stack.extend(values.iter_mut()),
+            serde_json::Value::Object(fields) => {
+                fields.sort_keys();
+                stack.extend(fields.values_mut());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Serialize a value as compact JSON with sorted object keys.
+pub fn to_string<T: serde::Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    let mut value = serde_json::to_value(value)?;
+    sort_keys(&mut value);
+
+    serde_json::to_string(&value)
+}
@@ -10,75 +10,676 @@ use std::collections::HashMap;
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("HTTP client error")]
-    Http(#[from] reqwest::Error),
+    Http(#[source] reqwest::Error),
+    #[error("Request timed out")]
+    Timeout(#[source] reqwest::Error),
     #[error("Invalid header")]
     Header(#[from] crate::request::HeaderError),
     #[error("Header value serialization error")]
     HeaderValueToStr(#[from] http::header::ToStrError),
+    #[error("JSON error")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid request")]
+    Request(#[from] http::Error),
     #[error("Unexpected status")]
+    #[non_exhaustive]
     UnexpectedStatus {
         status_code: http::StatusCode,
         body: Option<String>,
+        /// The delay requested by the `Retry-After` header, if there was one.
+        retry_after: Option<std::time::Duration>,
     },
 }
 
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Self::Timeout(error)
+        } else {
+            Self::Http(error)
+        }
+    }
+}
+
+/// Which HTTP versions the client may use.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum HttpVersionPref {
+    /// Negotiate the version with the server (the `reqwest` default).
+    #[default]
+    Negotiate,
+    Http1Only,
+    /// Use HTTP/2 without negotiation.
+    Http2PriorKnowledge,
+}
+
+/// Options for building a client.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ClientOptions {
+    pub http_version: HttpVersionPref,
+}
+
+impl ClientOptions {
+    pub fn apply(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        match self.http_version {
+            HttpVersionPref::Negotiate => builder,
+            HttpVersionPref::Http1Only => builder.http1_only(),
+            HttpVersionPref::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        }
+    }
+
+    pub fn build(&self) -> Result<reqwest::Client, Error> {
+        Ok(self.apply(reqwest::Client::builder()).build()?)
+    }
+}
+
+/// Send a request and parse the response as JSON.
+///
+/// The archived request has the URL `reqwest` used (which may be normalized), and any headers
+/// added while building the `reqwest` request. Headers that the client adds when sending (such as
+/// its default headers, or `Accept-Encoding`) are not included. See [`json_send_preserving`] to
+/// archive the original request instead.
 pub async fn json_send<'a>(
     client: &reqwest::Client,
     request: Request<'a>,
 ) -> Result<crate::exchange::Exchange<'a, serde_json::Value>, Error> {
-    let builder = build_request(client, &request)?;
-    let response = builder.send().await?;
-    let status_code = response.status();
-    let headers = response.headers();
-    let headers = response_headers_to_index_map(headers)?;
-
-    if status_code == StatusCode::OK {
-        let json = response.json().await?;
-
-        Ok(Exchange {
-            request,
-            response: Response {
-                headers,
-                data: json,
-            },
+    json_send_accepting(client, request, &[StatusCode::OK]).await
+}
+
+/// Options for sending a request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SendOptions {
+    /// The maximum time to wait for the whole response (the client's default applies if `None`).
+    ///
+    /// Timeouts result in [`Error::Timeout`].
+    pub timeout: Option<std::time::Duration>,
+    /// Replace invalid UTF-8 in response header values instead of failing with
+    /// [`Error::HeaderValueToStr`] (see [`response_headers_to_index_map_lossy`]).
+    pub lossy_headers: bool,
+    /// The statuses that are treated as success (only `200 OK` by default).
+    ///
+    /// Responses with other statuses result in [`Error::UnexpectedStatus`].
+    pub accept: Vec<StatusCode>,
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            lossy_headers: false,
+            accept: vec![StatusCode::OK],
+        }
+    }
+}
+
+/// Send a request and parse the response as JSON, with the given options.
+pub async fn json_send_with_options<'a>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+    options: &SendOptions,
+) -> Result<crate::exchange::Exchange<'a, serde_json::Value>, Error> {
+    send_json(client, request, false, false, options, |status| {
+        options.accept.contains(&status)
+    })
+    .await
+}
+
+/// Send a request and parse the response as JSON, treating any of the given statuses as success.
+///
+/// Responses with other statuses result in [`Error::UnexpectedStatus`]. The status is recorded in
+/// the archived response, and responses with an empty body (e.g. `204 No Content`) are archived
+/// with `null` data. This is a shortcut for [`json_send_with_options`] with
+/// [`SendOptions::accept`], which can be combined with the other options (e.g. a timeout).
+pub async fn json_send_accepting<'a>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+    accept: &[StatusCode],
+) -> Result<crate::exchange::Exchange<'a, serde_json::Value>, Error> {
+    let options = SendOptions {
+        accept: accept.to_vec(),
+        ..SendOptions::default()
+    };
+
+    json_send_with_options(client, request, &options).await
+}
+
+/// Send a request and parse the response as JSON, archiving the original request unchanged.
+pub async fn json_send_preserving<'a>(
+    client: &reqwest::Client,
+    original: Request<'a>,
+) -> Result<crate::exchange::Exchange<'a, serde_json::Value>, Error> {
+    send_json(
+        client,
+        original,
+        false,
+        true,
+        &SendOptions::default(),
+        |status| status == StatusCode::OK,
+    )
+    .await
+}
+
+/// When and how often to retry a request that failed with a transient status.
+#[cfg(feature = "tokio")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// The delay before the first retry, which doubles for each subsequent retry.
+    pub base_delay: std::time::Duration,
+    pub max_delay: std::time::Duration,
+    /// The statuses that are retried (any other status fails immediately).
+    pub retry_on: Vec<StatusCode>,
+}
+
+#[cfg(feature = "tokio")]
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: std::time::Duration::from_millis(500),
+            max_delay: std::time::Duration::from_secs(30),
+            retry_on: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl RetryPolicy {
+    /// The backoff before the given retry (counting from 0), without jitter.
+    #[must_use]
+    pub fn backoff(&self, retry: u32) -> std::time::Duration {
+        self.base_delay
+            .checked_mul(2_u32.saturating_pow(retry))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+
+    /// The backoff with jitter (a random delay between half and all of it).
+    fn backoff_with_jitter(&self, retry: u32) -> std::time::Duration {
+        use rand::RngExt;
+
+        let backoff = self.backoff(retry);
+
+        backoff / 2 + (backoff / 2).mul_f64(rand::rng().random_range(0.0..=1.0))
+    }
+}
+
+/// Send a request and parse the response as JSON, retrying transient failures.
+///
+/// Requests that fail with a status in the policy's `retry_on` list are retried after an
+/// exponential backoff with jitter, or after the delay given by the response's `Retry-After`
+/// header, if there is one. If every attempt fails, the last error is returned. Other errors are
+/// returned immediately, as are errors with a `Retry-After` delay longer than the policy's
+/// `max_delay`. Each attempt is sent with the given options, so a timeout applies per attempt.
+#[cfg(feature = "tokio")]
+pub async fn json_send_with_retry<'a>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+    policy: &RetryPolicy,
+    options: &SendOptions,
+) -> Result<crate::exchange::Exchange<'a, serde_json::Value>, Error> {
+    let mut retry = 0;
+
+    loop {
+        match json_send_with_options(client, request.clone(), options).await {
+            Err(Error::UnexpectedStatus {
+                status_code,
+                retry_after,
+                ..
+            }) if retry < policy.max_retries
+                && policy.retry_on.contains(&status_code)
+                && retry_after.is_none_or(|retry_after| retry_after <= policy.max_delay) =>
+            {
+                let delay = retry_after.unwrap_or_else(|| policy.backoff_with_jitter(retry));
+
+                tokio::time::sleep(delay).await;
+                retry += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// A per-host rate limiter for outgoing requests (a token bucket for each host).
+///
+/// Clones share their state, so a single limiter can be used from many tasks.
+#[cfg(feature = "tokio")]
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    interval: std::time::Duration,
+    burst: u32,
+    /// The time at which each host's bucket will be full again (the theoretical arrival time).
+    hosts: std::sync::Arc<std::sync::Mutex<HashMap<Option<String>, std::time::Instant>>>,
+}
+
+#[cfg(feature = "tokio")]
+impl RateLimiter {
+    /// Allow up to the given number of requests per second to each host.
+    ///
+    /// # Panics
+    ///
+    /// If `requests_per_second` is not positive and finite.
+    #[must_use]
+    pub fn new(requests_per_second: f64) -> Self {
+        Self {
+            interval: std::time::Duration::from_secs_f64(requests_per_second.recip()),
+            burst: 1,
+            hosts: std::sync::Arc::default(),
+        }
+    }
+
+    /// Allow up to `burst` requests to a host to be sent immediately after it has been idle.
+    #[must_use]
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Wait until a request can be sent to the given host.
+    ///
+    /// Each host has its own bucket, so waiting on one host never delays another. Requests without
+    /// a host (e.g. for `data:` URLs) share a single bucket.
+    pub async fn acquire(&self, host: Option<&str>) {
+        let delay = self.reserve(host, std::time::Instant::now());
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Take a token from the host's bucket, returning how long to wait until it is available.
+    fn reserve(&self, host: Option<&str>, now: std::time::Instant) -> std::time::Duration {
+        let mut hosts = self
+            .hosts
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        // Buckets that are already full again behave the same as new ones, so we drop them to
+        // keep the map from growing with every host ever seen.
+        hosts.retain(|_, full_at| *full_at > now);
+
+        let entry = hosts.entry(host.map(str::to_string)).or_insert(now);
+        *entry = (*entry).max(now) + self.interval;
+        let full_at = *entry;
+        drop(hosts);
+
+        // The bucket holds `burst` tokens, so we only wait once they have been used up.
+        full_at.saturating_duration_since(now + self.interval * self.burst)
+    }
+}
+
+/// Send a request and parse the response as JSON, waiting for the limiter to allow it first.
+#[cfg(feature = "tokio")]
+pub async fn json_send_limited<'a>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+    limiter: &RateLimiter,
+) -> Result<crate::exchange::Exchange<'a, serde_json::Value>, Error> {
+    limiter.acquire(request.url.host_str()).await;
+
+    json_send(client, request).await
+}
+
+/// Send a request and parse the response as JSON, with cookies from the jar.
+///
+/// Cookies that apply are added to the request's `Cookie` header (after any existing value), and
+/// cookies set by the response are stored in the jar for the URL of the final response (after any
+/// redirects). Cookies set by intermediate redirect responses are not seen (since `reqwest`
+/// follows redirects internally), and cookies set by responses with an unexpected status are not
+/// stored.
+///
+/// The archived request keeps the caller's original `Cookie` header, so that session values from
+/// the jar are never written to archives.
+pub async fn json_send_with_jar<'a>(
+    client: &reqwest::Client,
+    mut request: Request<'a>,
+    jar: &crate::cookie::CookieJar,
+) -> Result<crate::exchange::Exchange<'a, serde_json::Value>, Error> {
+    let original_cookies = request
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("cookie"))
+        .map(|(_, value)| value.clone());
+
+    if let Some(cookies) = jar.cookie_header(&request.url) {
+        let value = match request.header("cookie") {
+            Some(existing) => format!("{existing}; {cookies}"),
+            None => cookies,
+        };
+
+        request.set_header("Cookie", value);
+    }
+
+    let (mut exchange, final_url) = send_json_with_final_url(
+        client,
+        request,
+        false,
+        false,
+        &SendOptions::default(),
+        |status| status == StatusCode::OK,
+    )
+    .await?;
+
+    jar.store(&final_url, exchange.response.set_cookies());
+
+    match original_cookies {
+        Some(cookies) => exchange.request.set_header("Cookie", cookies),
+        None => exchange
+            .request
+            .headers
+            .retain(|name, _| !name.eq_ignore_ascii_case("cookie")),
+    }
+
+    Ok(exchange)
+}
+
+/// Send a request and parse the response as JSON, also keeping the exact response body.
+///
+/// The body is stored in the response's `raw` field, which allows byte-exact recovery of the
+/// captured response (re-serializing the parsed value may change key order, whitespace, etc.).
+pub async fn json_send_with_raw<'a>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+) -> Result<crate::exchange::Exchange<'a, serde_json::Value>, Error> {
+    send_json(
+        client,
+        request,
+        true,
+        false,
+        &SendOptions::default(),
+        |status| status == StatusCode::OK,
+    )
+    .await
+}
+
+/// Send a request and parse the response as JSON, using the cache if the request has already
+/// been sent.
+///
+/// Requests are identified by [`Request::cache_entry_key`], and only successful exchanges are
+/// cached.
+#[cfg(feature = "moka")]
+pub async fn cached_json_send(
+    client: &reqwest::Client,
+    request: Request<'_>,
+    cache: &moka::future::Cache<std::sync::Arc<str>, Exchange<'static, serde_json::Value>>,
+) -> Result<Exchange<'static, serde_json::Value>, Error> {
+    let key = request.cache_entry_key();
+
+    if let Some(exchange) = cache.get(&key).await {
+        Ok(exchange)
+    } else {
+        let exchange = json_send(client, request).await?.into_owned();
+        cache.insert(key, exchange.clone()).await;
+
+        Ok(exchange)
+    }
+}
+
+/// The result of sending two requests (e.g. to different versions of an API) and comparing the
+/// responses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComparisonReport {
+    pub status_a: StatusCode,
+    pub status_b: StatusCode,
+    pub header_differences: Vec<HeaderDifference>,
+    pub data_differences: Vec<crate::diff::Difference>,
+}
+
+/// A header whose values differ between two responses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HeaderDifference {
+    /// The lowercased header name.
+    pub name: String,
+    /// The values from each response (`None` if the header is absent).
+    pub values_a: Option<Vec<String>>,
+    pub values_b: Option<Vec<String>>,
+}
+
+impl ComparisonReport {
+    #[must_use]
+    pub fn status_equal(&self) -> bool {
+        self.status_a == self.status_b
+    }
+
+    /// Whether the responses had the same status and identical data (header differences are
+    /// ignored, since they typically include dates, request IDs, etc.).
+    #[must_use]
+    pub fn is_equivalent(&self) -> bool {
+        self.status_equal() && self.data_differences.is_empty()
+    }
+}
+
+/// Send two requests and compare their responses.
+///
+/// Responses with any status are accepted, but both must have JSON bodies.
+pub async fn compare<'a>(
+    client: &reqwest::Client,
+    request_a: Request<'a>,
+    request_b: Request<'a>,
+) -> Result<ComparisonReport, Error> {
+    let exchange_a = send_json(
+        client,
+        request_a,
+        false,
+        false,
+        &SendOptions::default(),
+        |_| true,
+    )
+    .await?;
+    let exchange_b = send_json(
+        client,
+        request_b,
+        false,
+        false,
+        &SendOptions::default(),
+        |_| true,
+    )
+    .await?;
+
+    let mut names = exchange_a
+        .response
+        .headers
+        .keys()
+        .chain(exchange_b.response.headers.keys())
+        .map(|name| name.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+
+    let header_values = |response: &Response<'_, serde_json::Value>, name: &str| {
+        response
+            .header(name)
+            .map(|values| values.iter().map(Cow::into_owned).collect::<Vec<_>>())
+    };
+
+    let header_differences = names
+        .into_iter()
+        .filter_map(|name| {
+            let values_a = header_values(&exchange_a.response, &name);
+            let values_b = header_values(&exchange_b.response, &name);
+
+            (values_a != values_b).then_some(HeaderDifference {
+                name,
+                values_a,
+                values_b,
+            })
         })
+        .collect();
+
+    Ok(ComparisonReport {
+        // The client always records the status.
+        status_a: exchange_a.response.status.unwrap_or_default(),
+        status_b: exchange_b.response.status.unwrap_or_default(),
+        header_differences,
+        data_differences: crate::diff::json(&exchange_a.response.data, &exchange_b.response.data),
+    })
+}
+
+async fn send_json<'a, F: Fn(StatusCode) -> bool>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+    keep_raw: bool,
+    preserve_original: bool,
+    options: &SendOptions,
+    accept: F,
+) -> Result<crate::exchange::Exchange<'a, serde_json::Value>, Error> {
+    send_json_with_final_url(
+        client,
+        request,
+        keep_raw,
+        preserve_original,
+        options,
+        accept,
+    )
+    .await
+    .map(|(exchange, _)| exchange)
+}
+
+/// Send a request and parse the response as JSON, also returning the URL of the final response
+/// (after any redirects).
+async fn send_json_with_final_url<'a, F: Fn(StatusCode) -> bool>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+    keep_raw: bool,
+    preserve_original: bool,
+    options: &SendOptions,
+    accept: F,
+) -> Result<(crate::exchange::Exchange<'a, serde_json::Value>, url::Url), Error> {
+    let sent = with_timeout(build_request(client, &request)?, options.timeout).build()?;
+    let request = if preserve_original {
+        request
+    } else {
+        with_sent(request, &sent)
+    };
+    let started = std::time::Instant::now();
+    let response = client.execute(sent).await?;
+    let status_code = response.status();
+    let http_version = response.version();
+    let headers = response_headers(response.headers(), options)?;
+
+    let final_url = response.url().clone();
+
+    if accept(status_code) {
+        let (json, raw) = if keep_raw {
+            let text = response.text().await?;
+
+            (parse_json_body(text.as_bytes())?, Some(text.into()))
+        } else {
+            (parse_json_body(&response.bytes().await?)?, None)
+        };
+
+        let mut response = Response::with_status(headers, json, status_code);
+        response.http_version = Some(http_version);
+        response.raw = raw;
+
+        let mut exchange = Exchange::new(request, response);
+        exchange.duration_ms = Some(elapsed_ms(started));
+
+        Ok((exchange, final_url))
     } else {
         // We attempt to retrieve the body for better error messages, but ignore any failure here.
+        let retry_after = retry_after(response.headers());
         let body = response.text().await.ok();
 
-        Err(Error::UnexpectedStatus { status_code, body })
+        Err(Error::UnexpectedStatus {
+            status_code,
+            body,
+            retry_after,
+        })
+    }
+}
+
+/// Parse a JSON response body, treating an empty body (e.g. for `204 No Content`) as `null`.
+fn parse_json_body(body: &[u8]) -> Result<serde_json::Value, serde_json::Error> {
+    if body.is_empty() {
+        Ok(serde_json::Value::Null)
+    } else {
+        serde_json::from_slice(body)
     }
 }
 
+/// Send a request and read the response body as text.
+///
+/// As with [`json_send`], only responses with status `200 OK` are accepted.
 pub async fn text_send<'a>(
     client: &reqwest::Client,
     request: Request<'a>,
 ) -> Result<crate::exchange::Exchange<'a, String>, Error> {
-    let builder = build_request(client, &request)?;
-    let response = builder.send().await?;
+    text_send_with_options(client, request, &SendOptions::default()).await
+}
+
+/// Send a request and read the response body as text, with the given options.
+pub async fn text_send_with_options<'a>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+    options: &SendOptions,
+) -> Result<crate::exchange::Exchange<'a, String>, Error> {
+    send_body(client, request, options, reqwest::Response::text).await
+}
+
+/// Send a request and read the response body as bytes (e.g. for binary formats like Protobuf).
+///
+/// As with [`json_send`], only responses with status `200 OK` are accepted.
+pub async fn bytes_send<'a>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+) -> Result<crate::exchange::Exchange<'a, Vec<u8>>, Error> {
+    bytes_send_with_options(client, request, &SendOptions::default()).await
+}
+
+/// Send a request and read the response body as bytes, with the given options.
+pub async fn bytes_send_with_options<'a>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+    options: &SendOptions,
+) -> Result<crate::exchange::Exchange<'a, Vec<u8>>, Error> {
+    send_body(client, request, options, |response| async move {
+        response.bytes().await.map(Vec::from)
+    })
+    .await
+}
+
+async fn send_body<'a, T, R: Future<Output = Result<T, reqwest::Error>>>(
+    client: &reqwest::Client,
+    request: Request<'a>,
+    options: &SendOptions,
+    read: impl FnOnce(reqwest::Response) -> R,
+) -> Result<crate::exchange::Exchange<'a, T>, Error> {
+    let sent = with_timeout(build_request(client, &request)?, options.timeout).build()?;
+    let request = with_sent(request, &sent);
+    let started = std::time::Instant::now();
+    let response = client.execute(sent).await?;
     let status_code = response.status();
-    let headers = response.headers();
-    let headers = response_headers_to_index_map(headers)?;
-
-    if status_code == StatusCode::OK {
-        let text = response.text().await?;
-
-        Ok(Exchange {
-            request,
-            response: Response {
-                headers,
-                data: text,
-            },
-        })
+    let http_version = response.version();
+    let headers = response_headers(response.headers(), options)?;
+
+    if options.accept.contains(&status_code) {
+        let data = read(response).await?;
+
+        let mut response = Response::with_status(headers, data, status_code);
+        response.http_version = Some(http_version);
+
+        let mut exchange = Exchange::new(request, response);
+        exchange.duration_ms = Some(elapsed_ms(started));
+
+        Ok(exchange)
     } else {
         // We attempt to retrieve the body for better error messages, but ignore any failure here.
+        let retry_after = retry_after(response.headers());
         let body = response.text().await.ok();
 
-        Err(Error::UnexpectedStatus { status_code, body })
+        Err(Error::UnexpectedStatus {
+            status_code,
+            body,
+            retry_after,
+        })
     }
 }
 
-fn build_request<'a>(
+pub(crate) fn build_request<'a>(
     client: &reqwest::Client,
     request: &'a Request<'a>,
 ) -> Result<reqwest::RequestBuilder, crate::request::HeaderError> {
@@ -93,24 +694,735 @@ fn build_request<'a>(
     })
 }
 
+/// Parse a `Retry-After` header, which may be a number of seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<std::time::Duration> {
+    let value = headers
+        .get(http::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+
+    value
+        .parse()
+        .map(std::time::Duration::from_secs)
+        .ok()
+        .or_else(|| {
+            let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+
+            // A date in the past means that we can retry immediately.
+            Some(
+                (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                    .to_std()
+                    .unwrap_or_default(),
+            )
+        })
+}
+
+fn with_timeout(
+    builder: reqwest::RequestBuilder,
+    timeout: Option<std::time::Duration>,
+) -> reqwest::RequestBuilder {
+    match timeout {
+        Some(timeout) => builder.timeout(timeout),
+        None => builder,
+    }
+}
+
+fn elapsed_ms(started: std::time::Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Update a request to match the realized `reqwest` request that will be sent for it.
+///
+/// Existing header names keep their original case, and added headers are appended. The client's
+/// default headers are only added when the request is executed, so they are not included.
+fn with_sent<'a>(mut request: Request<'a>, sent: &reqwest::Request) -> Request<'a> {
+    request.url = sent.url().clone();
+
+    for (name, value) in sent.headers() {
+        if request.header(name.as_str()).is_none() {
+            request.headers.insert(
+                name.as_str().to_string().into(),
+                String::from_utf8_lossy(value.as_bytes())
+                    .into_owned()
+                    .into(),
+            );
+        }
+    }
+
+    request
+}
+
+/// Convert response headers into the archived representation, as configured by the options.
+fn response_headers(
+    response_headers: &HeaderMap,
+    options: &SendOptions,
+) -> Result<HashMap<Cow<'static, str>, MultiValue<'static>>, http::header::ToStrError> {
+    if options.lossy_headers {
+        Ok(response_headers_to_index_map_lossy(response_headers))
+    } else {
+        response_headers_to_index_map(response_headers)
+    }
+}
+
 fn response_headers_to_index_map(
     response_headers: &HeaderMap,
 ) -> Result<HashMap<Cow<'static, str>, MultiValue<'static>>, http::header::ToStrError> {
+    collect_headers(response_headers, |value| {
+        value.to_str().map(std::string::ToString::to_string)
+    })
+}
+
+/// Convert response headers into the archived representation, replacing invalid UTF-8 instead
+/// of failing.
+#[must_use]
+pub fn response_headers_to_index_map_lossy(
+    response_headers: &HeaderMap,
+) -> HashMap<Cow<'static, str>, MultiValue<'static>> {
+    let result: Result<_, std::convert::Infallible> = collect_headers(response_headers, |value| {
+        Ok(String::from_utf8_lossy(value.as_bytes()).into_owned())
+    });
+
+    match result {
+        Ok(headers) => headers,
+        Err(error) => match error {},
+    }
+}
+
+fn collect_headers<E, F: Fn(&http::HeaderValue) -> Result<String, E>>(
+    response_headers: &HeaderMap,
+    f: F,
+) -> Result<HashMap<Cow<'static, str>, MultiValue<'static>>, E> {
     let mut result: HashMap<Cow<'static, str>, MultiValue<'static>> = HashMap::new();
 
     for (name, value) in response_headers {
-        let value = value.to_str()?;
+        let value = f(value)?;
 
         match result.entry(name.as_str().to_string().into()) {
             std::collections::hash_map::Entry::Occupied(mut entry) => {
                 let multi_value = entry.get_mut();
-                multi_value.push(value.to_string());
+                multi_value.push(value);
             }
             std::collections::hash_map::Entry::Vacant(entry) => {
-                entry.insert(MultiValue::new(value.to_string()));
+                entry.insert(MultiValue::new(value));
             }
         }
     }
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "tokio")]
+    use super::RetryPolicy;
+    use crate::request::Request;
+    use std::time::Duration;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_server(response: ResponseTemplate) -> MockServer {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    fn mock_request(server: &MockServer) -> Result<Request<'static>, url::ParseError> {
+        Request::builder(format!("{}/api?q=a%20b", server.uri()))
+            .header("X-Request", "1")
+            .build()
+    }
+
+    #[tokio::test]
+    async fn json_send_archived_headers() -> Result<(), Box<dyn std::error::Error>> {
+        let server =
+            mock_server(ResponseTemplate::new(200).set_body_json(serde_json::json!({"a": 1})))
+                .await;
+        let client = reqwest::Client::builder()
+            .default_headers(http::HeaderMap::from_iter([(
+                http::HeaderName::from_static("x-default"),
+                http::HeaderValue::from_static("2"),
+            )]))
+            .build()?;
+
+        let exchange = super::json_send(&client, mock_request(&server)?).await?;
+
+        assert_eq!(exchange.response.data, serde_json::json!({"a": 1}));
+        assert_eq!(exchange.response.status, Some(http::StatusCode::OK));
+        assert_eq!(exchange.request.header("x-request"), Some("1"));
+        // The default header is sent, but added too late to be archived.
+        assert_eq!(exchange.request.header("x-default"), None);
+
+        let received = server.received_requests().await.ok_or("missing requests")?;
+
+        assert_eq!(
+            received[0]
+                .headers
+                .get("x-default")
+                .map(http::HeaderValue::as_bytes),
+            Some(b"2".as_slice())
+        );
+
+        let original = mock_request(&server)?;
+        let exchange = super::json_send_preserving(&client, original.clone()).await?;
+
+        assert_eq!(exchange.request, original);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn json_send_accepting() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/items"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({"id": 1})))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/items"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let accept = [http::StatusCode::CREATED, http::StatusCode::NO_CONTENT];
+        let request = |method| {
+            Request::builder(format!("{}/items", server.uri()))
+                .method(method)
+                .build()
+        };
+
+        let created =
+            super::json_send_accepting(&client, request(http::Method::POST)?, &accept).await?;
+
+        assert_eq!(created.response.status, Some(http::StatusCode::CREATED));
+        assert_eq!(created.response.data, serde_json::json!({"id": 1}));
+
+        let deleted =
+            super::json_send_accepting(&client, request(http::Method::DELETE)?, &accept).await?;
+
+        assert_eq!(deleted.response.status, Some(http::StatusCode::NO_CONTENT));
+        assert_eq!(deleted.response.data, serde_json::Value::Null);
+
+        assert!(matches!(
+            super::json_send(&client, request(http::Method::POST)?).await,
+            Err(super::Error::UnexpectedStatus {
+                status_code: http::StatusCode::CREATED,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "moka")]
+    #[tokio::test]
+    async fn cached_json_send() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"a": 1})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/error"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let cache = moka::future::Cache::new(16);
+
+        let first = super::cached_json_send(&client, mock_request(&server)?, &cache).await?;
+        let second = super::cached_json_send(&client, mock_request(&server)?, &cache).await?;
+
+        assert_eq!(first.response.data, serde_json::json!({"a": 1}));
+        assert_eq!(second, first);
+
+        let error = Request::builder(format!("{}/error", server.uri())).build()?;
+
+        for _ in 0..2 {
+            assert!(
+                super::cached_json_send(&client, error.clone(), &cache)
+                    .await
+                    .is_err()
+            );
+        }
+
+        // Only successful exchanges are cached.
+        let received = server.received_requests().await.ok_or("missing requests")?;
+
+        assert_eq!(
+            received
+                .iter()
+                .map(|request| request.url.path())
+                .collect::<Vec<_>>(),
+            vec!["/api", "/error", "/error"]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn compare() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("X-Version", "1")
+                    .set_body_json(serde_json::json!({"a": 1, "b": 2})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2"))
+            .respond_with(
+                ResponseTemplate::new(404)
+                    .insert_header("X-Version", "2")
+                    .set_body_json(serde_json::json!({"a": 1, "b": 3})),
+            )
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let request =
+            |version: &str| Request::builder(format!("{}/{version}", server.uri())).build();
+
+        let report = super::compare(&client, request("v1")?, request("v2")?).await?;
+
+        assert_eq!(report.status_a, http::StatusCode::OK);
+        assert_eq!(report.status_b, http::StatusCode::NOT_FOUND);
+        assert!(!report.status_equal());
+        assert!(!report.is_equivalent());
+        assert_eq!(
+            report
+                .header_differences
+                .iter()
+                .find(|difference| difference.name == "x-version"),
+            Some(&super::HeaderDifference {
+                name: "x-version".to_string(),
+                values_a: Some(vec!["1".to_string()]),
+                values_b: Some(vec!["2".to_string()]),
+            })
+        );
+        assert!(
+            report
+                .header_differences
+                .iter()
+                .all(|difference| difference.name != "content-type")
+        );
+        assert_eq!(
+            report.data_differences,
+            vec![crate::diff::Difference {
+                pointer: "/b".to_string(),
+                left: Some(serde_json::json!(2)),
+                right: Some(serde_json::json!(3)),
+            }]
+        );
+
+        let report = super::compare(&client, request("v1")?, request("v1")?).await?;
+
+        assert!(report.is_equivalent());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn json_send_with_raw() -> Result<(), Box<dyn std::error::Error>> {
+        let body = "{ \"b\": 1,\n  \"a\": [true] }";
+        let server = mock_server(
+            ResponseTemplate::new(200).set_body_raw(body.as_bytes(), "application/json"),
+        )
+        .await;
+
+        let exchange =
+            super::json_send_with_raw(&reqwest::Client::new(), mock_request(&server)?).await?;
+
+        assert_eq!(
+            exchange.response.data,
+            serde_json::json!({"a": [true], "b": 1})
+        );
+        // The exact body is kept, including key order and whitespace.
+        assert_eq!(exchange.response.raw.as_deref(), Some(body));
+
+        let exchange = super::json_send(&reqwest::Client::new(), mock_request(&server)?).await?;
+
+        assert_eq!(exchange.response.raw, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn bytes_and_text_send() -> Result<(), Box<dyn std::error::Error>> {
+        let body = [0x08, 0x96, 0x01, 0xff];
+        let server = mock_server(
+            ResponseTemplate::new(200).set_body_raw(body.as_slice(), "application/x-protobuf"),
+        )
+        .await;
+        let client = reqwest::Client::new();
+
+        let exchange = super::bytes_send(&client, mock_request(&server)?).await?;
+
+        assert_eq!(exchange.response.data, body);
+        assert_eq!(exchange.response.status, Some(http::StatusCode::OK));
+        assert_eq!(
+            exchange
+                .response
+                .header("content-type")
+                .map(|values| values.first.as_ref()),
+            Some("application/x-protobuf")
+        );
+        assert_eq!(exchange.request.header("x-request"), Some("1"));
+
+        let server = mock_server(ResponseTemplate::new(200).set_body_string("<p>hi</p>")).await;
+        let exchange = super::text_send(&client, mock_request(&server)?).await?;
+
+        assert_eq!(exchange.response.data, "<p>hi</p>");
+
+        let server = mock_server(ResponseTemplate::new(404).set_body_string("missing")).await;
+
+        assert!(matches!(
+            super::bytes_send(&client, mock_request(&server)?).await,
+            Err(super::Error::UnexpectedStatus {
+                status_code: http::StatusCode::NOT_FOUND,
+                body: Some(body),
+                ..
+            }) if body == "missing"
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn json_send_lossy_headers() -> Result<(), Box<dyn std::error::Error>> {
+        let server = mock_server(
+            ResponseTemplate::new(200)
+                .insert_header(
+                    "Content-Disposition",
+                    http::HeaderValue::from_bytes(b"caf\xe9")?,
+                )
+                .set_body_json(serde_json::json!({})),
+        )
+        .await;
+        let client = reqwest::Client::new();
+
+        assert!(matches!(
+            super::json_send(&client, mock_request(&server)?).await,
+            Err(super::Error::HeaderValueToStr(_))
+        ));
+
+        let options = super::SendOptions {
+            lossy_headers: true,
+            ..super::SendOptions::default()
+        };
+        let exchange =
+            super::json_send_with_options(&client, mock_request(&server)?, &options).await?;
+
+        assert_eq!(
+            exchange
+                .response
+                .headers
+                .get("content-disposition")
+                .map(|value| value.first.as_ref()),
+            Some("caf\u{fffd}")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn json_send_with_jar() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        let port = server.address().port();
+
+        Mock::given(method("GET"))
+            .and(path("/login"))
+            .respond_with(
+                ResponseTemplate::new(302)
+                    .insert_header("Location", format!("http://localhost:{port}/home").as_str()),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/home"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Set-Cookie", "session=abc; Path=/")
+                    .set_body_json(serde_json::json!({})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let jar = crate::cookie::CookieJar::new();
+        let login = Request::builder(format!("http://127.0.0.1:{port}/login")).build()?;
+
+        let exchange = super::json_send_with_jar(&client, login, &jar).await?;
+
+        assert_eq!(exchange.request.header("cookie"), None);
+
+        // The cookie was set after a redirect to another host, so it belongs to that host.
+        assert_eq!(
+            jar.cookie_header(&format!("http://localhost:{port}/api").parse()?)
+                .as_deref(),
+            Some("session=abc")
+        );
+        assert_eq!(
+            jar.cookie_header(&format!("http://127.0.0.1:{port}/api").parse()?),
+            None
+        );
+
+        let api = Request::builder(format!("http://localhost:{port}/api"))
+            .header("Cookie", "a=1")
+            .build()?;
+        let exchange = super::json_send_with_jar(&client, api, &jar).await?;
+
+        // The session cookie from the jar is sent but not archived.
+        assert_eq!(exchange.request.header("cookie"), Some("a=1"));
+
+        let received = server.received_requests().await.ok_or("missing requests")?;
+
+        assert_eq!(
+            received
+                .last()
+                .and_then(|request| request.headers.get("cookie"))
+                .map(http::HeaderValue::as_bytes),
+            Some(b"a=1; session=abc".as_slice())
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn json_send_with_retry() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"a": 1})))
+            .mount(&server)
+            .await;
+
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(10),
+            ..RetryPolicy::default()
+        };
+        let exchange = super::json_send_with_retry(
+            &reqwest::Client::new(),
+            mock_request(&server)?,
+            &policy,
+            &super::SendOptions::default(),
+        )
+        .await?;
+
+        assert_eq!(exchange.response.data, serde_json::json!({"a": 1}));
+        assert_eq!(
+            server
+                .received_requests()
+                .await
+                .map(|requests| requests.len()),
+            Some(2)
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn json_send_with_retry_long_retry_after() -> Result<(), Box<dyn std::error::Error>> {
+        let server =
+            mock_server(ResponseTemplate::new(503).insert_header("Retry-After", "86400")).await;
+
+        let result = super::json_send_with_retry(
+            &reqwest::Client::new(),
+            mock_request(&server)?,
+            &RetryPolicy::default(),
+            &super::SendOptions::default(),
+        )
+        .await;
+
+        // The requested delay is longer than the maximum, so we fail without waiting.
+        assert!(matches!(
+            result,
+            Err(super::Error::UnexpectedStatus {
+                status_code: http::StatusCode::SERVICE_UNAVAILABLE,
+                retry_after: Some(_),
+                ..
+            })
+        ));
+        assert_eq!(
+            server
+                .received_requests()
+                .await
+                .map(|requests| requests.len()),
+            Some(1)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn send_with_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        let server = mock_server(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({}))
+                .set_delay(Duration::from_secs(5)),
+        )
+        .await;
+        let client = reqwest::Client::new();
+        let options = super::SendOptions {
+            timeout: Some(Duration::from_millis(50)),
+            ..super::SendOptions::default()
+        };
+
+        assert!(matches!(
+            super::text_send_with_options(&client, mock_request(&server)?, &options).await,
+            Err(super::Error::Timeout(_))
+        ));
+        assert!(matches!(
+            super::bytes_send_with_options(&client, mock_request(&server)?, &options).await,
+            Err(super::Error::Timeout(_))
+        ));
+        assert!(matches!(
+            super::json_send_with_options(&client, mock_request(&server)?, &options).await,
+            Err(super::Error::Timeout(_))
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn json_send_with_retry_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        let server =
+            mock_server(ResponseTemplate::new(503).set_delay(Duration::from_secs(5))).await;
+        let options = super::SendOptions {
+            timeout: Some(Duration::from_millis(50)),
+            ..super::SendOptions::default()
+        };
+
+        let result = super::json_send_with_retry(
+            &reqwest::Client::new(),
+            mock_request(&server)?,
+            &RetryPolicy::default(),
+            &options,
+        )
+        .await;
+
+        // Timeouts are not retried.
+        assert!(matches!(result, Err(super::Error::Timeout(_))));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(4), Duration::from_secs(1));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(1));
+
+        for retry in 0..5 {
+            let delay = policy.backoff_with_jitter(retry);
+
+            assert!(delay >= policy.backoff(retry) / 2 && delay <= policy.backoff(retry));
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn rate_limiter() {
+        let limiter = super::RateLimiter::new(2.0).with_burst(2);
+        let shared = limiter.clone();
+        let now = std::time::Instant::now();
+        let delays = (0..4)
+            .map(|_| shared.reserve(Some("example.com"), now))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            delays,
+            vec![
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::from_millis(500),
+                Duration::from_secs(1)
+            ]
+        );
+
+        // Other hosts are not affected.
+        assert_eq!(limiter.reserve(Some("example.org"), now), Duration::ZERO);
+        assert_eq!(limiter.reserve(None, now), Duration::ZERO);
+
+        // After an idle period only the burst is available immediately.
+        let later = now + Duration::from_mins(1);
+
+        assert_eq!(limiter.reserve(Some("example.com"), later), Duration::ZERO);
+        assert_eq!(limiter.reserve(Some("example.com"), later), Duration::ZERO);
+        assert_eq!(
+            limiter.reserve(Some("example.com"), later),
+            Duration::from_millis(500)
+        );
+
+        // Hosts whose buckets refilled during the idle period have been dropped.
+        assert_eq!(
+            limiter
+                .hosts
+                .lock()
+                .map(|hosts| hosts.keys().cloned().collect::<Vec<_>>())
+                .ok(),
+            Some(vec![Some("example.com".to_string())])
+        );
+    }
+
+    #[test]
+    fn retry_after() -> Result<(), Box<dyn std::error::Error>> {
+        let mut headers = http::HeaderMap::new();
+
+        assert_eq!(super::retry_after(&headers), None);
+
+        headers.insert(http::header::RETRY_AFTER, "90".parse()?);
+        assert_eq!(super::retry_after(&headers), Some(Duration::from_secs(90)));
+
+        headers.insert(
+            http::header::RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse()?,
+        );
+        assert_eq!(super::retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(http::header::RETRY_AFTER, "soon".parse()?);
+        assert_eq!(super::retry_after(&headers), None);
+
+        Ok(())
+    }
+}
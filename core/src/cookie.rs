@@ -0,0 +1,318 @@
+use chrono::{DateTime, Utc};
+use url::Url;
+
+/// A cookie from a `Set-Cookie` response header (see RFC 6265, section 5.2).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// The `Domain` attribute, without any leading dot.
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    pub expires: Option<DateTime<Utc>>,
+    /// The `Max-Age` attribute, in seconds.
+    pub max_age: Option<i64>,
+    pub secure: bool,
+    pub http_only: bool,
+}
+
+impl Cookie {
+    /// Parse a `Set-Cookie` header value.
+    ///
+    /// Unknown attributes and attributes with invalid values are ignored. Returns `None` if the
+    /// value does not start with a `name=value` pair.
+    #[must_use]
+    pub fn parse_set_cookie(value: &str) -> Option<Self> {
+        let mut parts = value.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Self {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: None,
+            path: None,
+            expires: None,
+            max_age: None,
+            secure: false,
+            http_only: false,
+        };
+
+        for attribute in parts {
+            let (name, value) = attribute
+                .split_once('=')
+                .map_or((attribute, ""), |(name, value)| (name, value));
+            let value = value.trim();
+
+            match name.trim().to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    cookie.domain = Some(value.trim_start_matches('.').to_ascii_lowercase());
+                }
+                "path" if value.starts_with('/') => cookie.path = Some(value.to_string()),
+                "expires" => {
+                    cookie.expires = DateTime::parse_from_rfc2822(value)
+                        .ok()
+                        .map(|expires| expires.with_timezone(&Utc));
+                }
+                "max-age" => cookie.max_age = value.parse().ok(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                _ => {}
+            }
+        }
+
+        Some(cookie)
+    }
+}
+
+/// A cookie store for sending cookies set by earlier responses (see RFC 6265, section 5.3).
+///
+/// Clones share their state, so a single jar can be used from many tasks. The `Domain`, `Path`,
+/// `Secure`, `Expires`, and `Max-Age` attributes are respected, but there is no public suffix
+/// list, and no limits on the number of cookies.
+#[derive(Clone, Debug, Default)]
+pub struct CookieJar {
+    cookies: std::sync::Arc<std::sync::Mutex<Vec<StoredCookie>>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    domain: String,
+    /// Whether the cookie had no `Domain` attribute, and so is only sent to the exact host.
+    host_only: bool,
+    path: String,
+    secure: bool,
+    expires: Option<DateTime<Utc>>,
+}
+
+impl CookieJar {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store cookies from a response to a request for the given URL.
+    ///
+    /// Cookies replace any stored cookie with the same name, domain, and path, and expired cookies
+    /// remove it. Cookies with a `Domain` attribute that does not match the URL's host are
+    /// ignored.
+    pub fn store<I: IntoIterator<Item = Cookie>>(&self, url: &Url, cookies: I) {
+        self.store_at(url, cookies, Utc::now());
+    }
+
+    /// The value of the `Cookie` header to send with a request for the given URL, if any cookies
+    /// apply.
+    #[must_use]
+    pub fn cookie_header(&self, url: &Url) -> Option<String> {
+        self.cookie_header_at(url, Utc::now())
+    }
+
+    /// The number of stored cookies (including any that have expired but not yet been removed).
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn store_at<I: IntoIterator<Item = Cookie>>(&self, url: &Url, cookies: I, now: DateTime<Utc>) {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return;
+        };
+
+        let mut stored = self.lock();
+
+        for cookie in cookies {
+            let (domain, host_only) = match cookie.domain {
+                Some(domain) if domain_matches(&host, &domain) => (domain, false),
+                Some(_) => continue,
+                None => (host.clone(), true),
+            };
+            let expires = cookie.max_age.map_or(cookie.expires, |max_age| {
+                Some(now + chrono::TimeDelta::seconds(max_age.max(0)))
+            });
+            let cookie = StoredCookie {
+                name: cookie.name,
+                value: cookie.value,
+                domain,
+                host_only,
+                path: cookie.path.unwrap_or_else(|| default_path(url)),
+                secure: cookie.secure,
+                expires,
+            };
+
+            stored.retain(|existing| {
+                existing.name != cookie.name
+                    || existing.domain != cookie.domain
+                    || existing.path != cookie.path
+            });
+
+            if cookie.expires.is_none_or(|expires| expires > now) {
+                stored.push(cookie);
+            }
+        }
+    }
+
+    fn cookie_header_at(&self, url: &Url, now: DateTime<Utc>) -> Option<String> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let mut stored = self.lock();
+
+        stored.retain(|cookie| cookie.expires.is_none_or(|expires| expires > now));
+
+        let mut cookies = stored
+            .iter()
+            .filter(|cookie| {
+                (if cookie.host_only {
+                    host == cookie.domain
+                } else {
+                    domain_matches(&host, &cookie.domain)
+                }) && path_matches(url.path(), &cookie.path)
+                    && (!cookie.secure || url.scheme() == "https")
+            })
+            .map(|cookie| {
+                (
+                    cookie.path.len(),
+                    format!("{}={}", cookie.name, cookie.value),
+                )
+            })
+            .collect::<Vec<_>>();
+        drop(stored);
+
+        // Cookies with longer paths are listed first.
+        cookies.sort_by_key(|(path_len, _)| std::cmp::Reverse(*path_len));
+
+        (!cookies.is_empty()).then(|| {
+            cookies
+                .into_iter()
+                .map(|(_, pair)| pair)
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<StoredCookie>> {
+        self.cookies
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// See RFC 6265, section 5.1.3 (`domain` must already be lowercase).
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+            && host.parse::<std::net::IpAddr>().is_err())
+}
+
+/// See RFC 6265, section 5.1.4.
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path
+        .strip_prefix(cookie_path)
+        .is_some_and(|rest| rest.is_empty() || cookie_path.ends_with('/') || rest.starts_with('/'))
+}
+
+/// See RFC 6265, section 5.1.4.
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => url.path()[..index].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cookie, CookieJar};
+
+    #[test]
+    fn parse_set_cookie() {
+        let cookie = Cookie::parse_set_cookie(
+            "ASP.NET_SessionId=abc=1; path=/app; Domain=.Example.com; Expires=Wed, 21 Oct 2015 07:28:00 GMT; HttpOnly; Max-Age=60",
+        );
+
+        assert_eq!(
+            cookie,
+            Some(Cookie {
+                name: "ASP.NET_SessionId".to_string(),
+                value: "abc=1".to_string(),
+                domain: Some("example.com".to_string()),
+                path: Some("/app".to_string()),
+                expires: "2015-10-21T07:28:00Z".parse().ok(),
+                max_age: Some(60),
+                secure: false,
+                http_only: true,
+            })
+        );
+        assert_eq!(Cookie::parse_set_cookie("invalid; Path=/"), None);
+    }
+
+    #[test]
+    fn cookie_jar() -> Result<(), Box<dyn std::error::Error>> {
+        let jar = CookieJar::new();
+        let shared = jar.clone();
+        let now = chrono::Utc::now();
+        let login = "https://www.example.com/account/login".parse()?;
+        let cookies = [
+            "session=abc; Path=/",
+            "account=1",
+            "wide=x; Domain=example.com; Path=/; Secure",
+            "short=y; Max-Age=60",
+            "other=z; Domain=example.org",
+        ];
+
+        shared.store_at(
+            &login,
+            cookies.into_iter().filter_map(Cookie::parse_set_cookie),
+            now,
+        );
+
+        assert_eq!(jar.len(), 4);
+        assert_eq!(
+            jar.cookie_header_at(&"https://www.example.com/account/settings".parse()?, now)
+                .as_deref(),
+            Some("account=1; short=y; session=abc; wide=x")
+        );
+        assert_eq!(
+            jar.cookie_header_at(&"http://api.example.com/".parse()?, now),
+            None
+        );
+        assert_eq!(
+            jar.cookie_header_at(&"https://api.example.com/".parse()?, now)
+                .as_deref(),
+            Some("wide=x")
+        );
+
+        let later = now + chrono::TimeDelta::minutes(2);
+
+        assert_eq!(
+            jar.cookie_header_at(&"https://www.example.com/".parse()?, later)
+                .as_deref(),
+            Some("session=abc; wide=x")
+        );
+
+        jar.store_at(
+            &login,
+            Cookie::parse_set_cookie("session=; Path=/; Max-Age=0"),
+            later,
+        );
+
+        assert_eq!(
+            jar.cookie_header_at(&"https://www.example.com/".parse()?, later)
+                .as_deref(),
+            Some("wide=x")
+        );
+        assert_eq!(jar.len(), 2);
+
+        Ok(())
+    }
+}
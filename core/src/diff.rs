@@ -0,0 +1,107 @@
+use serde_json::Value;
+
+/// A single difference between two JSON values.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Difference {
+    /// A JSON pointer to the location of the difference.
+    pub pointer: String,
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+/// Compute the differences between two JSON values.
+///
+/// Objects are compared by key and arrays by index. Any other mismatch (including a change of
+/// type) is reported at the location where it occurs.
+#[must_use]
+pub fn json(left: &Value, right: &Value) -> Vec<Difference> {
+    let mut differences = vec![];
+    let mut stack = vec![(String::new(), Some(left), Some(right))];
+
+    while let Some((pointer, left, right)) = stack.pop() {
+        match (left, right) {
+            (Some(Value::Object(left)), Some(Value::Object(right))) => {
+                for (key, left_value) in left {
+                    stack.push((
+                        child_pointer(&pointer, key),
+                        Some(left_value),
+                        right.get(key),
+                    ));
+                }
+
+                for (key, right_value) in right {
+                    if !left.contains_key(key) {
+                        stack.push((child_pointer(&pointer, key), None, Some(right_value)));
+                    }
+                }
+            }
+            (Some(Value::Array(left)), Some(Value::Array(right))) => {
+                for index in 0..left.len().max(right.len()) {
+                    stack.push((
+                        child_pointer(&pointer, &index.to_string()),
+                        left.get(index),
+                        right.get(index),
+                    ));
+                }
+            }
+            (left, right) if left != right => {
+                differences.push(Difference {
+                    pointer,
+                    left: left.cloned(),
+                    right: right.cloned(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    differences.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+    differences
+}
+
+fn child_pointer(pointer: &str, key: &str) -> String {
+    format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Difference;
+    use serde_json::json;
+
+    #[test]
+    fn json_diff() {
+        let left = json!({"a": 1, "b": [1, 2, 3], "c": {"d/e": true}, "f": null});
+        let right = json!({"a": 1, "b": [1, 4], "c": {"d/e": false}, "g": "new"});
+
+        assert_eq!(
+            super::json(&left, &right),
+            vec![
+                Difference {
+                    pointer: "/b/1".to_string(),
+                    left: Some(json!(2)),
+                    right: Some(json!(4)),
+                },
+                Difference {
+                    pointer: "/b/2".to_string(),
+                    left: Some(json!(3)),
+                    right: None,
+                },
+                Difference {
+                    pointer: "/c/d~1e".to_string(),
+                    left: Some(json!(true)),
+                    right: Some(json!(false)),
+                },
+                Difference {
+                    pointer: "/f".to_string(),
+                    left: Some(json!(null)),
+                    right: None,
+                },
+                Difference {
+                    pointer: "/g".to_string(),
+                    left: None,
+                    right: Some(json!("new")),
+                },
+            ]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{multi_value::MultiValue, request::Request};
 use bounded_static::{IntoBoundedStatic, ToBoundedStatic};
+use http::{StatusCode, Version};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -12,20 +13,80 @@ pub enum Error {
     RequestHeaderValue(#[from] http::header::InvalidHeaderValue),
     #[error("Invalid response header value")]
     ResponseHeaderValue(#[from] http::header::ToStrError),
+    #[error("JSON error")]
+    Json(#[from] serde_json::Error),
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    #[error("Unsupported content encoding")]
+    UnsupportedContentEncoding(String),
+    #[cfg(feature = "binary")]
+    #[error("Binary encoding error")]
+    BinaryEncode(#[from] rmp_serde::encode::Error),
+    #[cfg(feature = "binary")]
+    #[error("Binary decoding error")]
+    BinaryDecode(#[from] rmp_serde::decode::Error),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[non_exhaustive]
 pub struct Exchange<'a, T> {
     #[serde(borrow)]
     pub request: Request<'a>,
     pub response: Response<'a, T>,
+    /// Labels for organizing exchanges within a store.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Time from sending the request to reading the full response body, in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
 }
 
 impl<'a, T> Exchange<'a, T> {
+    pub const fn new(request: Request<'a>, response: Response<'a, T>) -> Self {
+        Self {
+            request,
+            response,
+            tags: vec![],
+            duration_ms: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_tags<S: Into<String>, I: IntoIterator<Item = S>>(mut self, tags: I) -> Self {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|value| value == tag)
+    }
+
+    #[must_use]
+    pub fn duration(&self) -> Option<std::time::Duration> {
+        self.duration_ms.map(std::time::Duration::from_millis)
+    }
+
     pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Exchange<'a, U> {
         Exchange {
             request: self.request,
             response: self.response.map(f),
+            tags: self.tags,
+            duration_ms: self.duration_ms,
+        }
+    }
+}
+
+impl<T: 'static> Exchange<'_, T> {
+    /// Convert into a `'static` exchange for data types that are already owned (such as
+    /// `serde_json::Value`) but do not implement `IntoBoundedStatic`.
+    #[must_use]
+    pub fn into_owned(self) -> Exchange<'static, T> {
+        Exchange {
+            request: self.request.into_static(),
+            response: self.response.into_owned(),
+            tags: self.tags,
+            duration_ms: self.duration_ms,
         }
     }
 }
@@ -37,6 +98,8 @@ impl<'a, T: IntoBoundedStatic + 'a> IntoBoundedStatic for Exchange<'a, T> {
         Self::Static {
             request: self.request.into_static(),
             response: self.response.into_static(),
+            tags: self.tags,
+            duration_ms: self.duration_ms,
         }
     }
 }
@@ -48,41 +111,467 @@ impl<T: ToBoundedStatic> ToBoundedStatic for Exchange<'_, T> {
         Self::Static {
             request: self.request.to_static(),
             response: self.response.to_static(),
+            tags: self.tags.clone(),
+            duration_ms: self.duration_ms,
+        }
+    }
+}
+
+impl<T> Exchange<'_, T> {
+    /// Redact the named headers in both the request and the response (see [`Request::redact`]).
+    pub fn redact(&mut self, names: &[&str]) {
+        self.request.redact(names);
+        self.response.redact(names);
+    }
+
+    /// The exact response body, if it was captured (see [`crate::client::json_send_with_raw`]).
+    #[must_use]
+    pub fn response_raw(&self) -> Option<&str> {
+        self.response.raw.as_deref()
+    }
+}
+
+impl<T: serde::de::DeserializeOwned + 'static> Exchange<'static, T> {
+    /// Read an exchange from a file (e.g. one written by [`Exchange::save_file`]).
+    ///
+    /// Files ending in `.gz` are decompressed.
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let contents = read_file(path.as_ref())?;
+
+        Ok(serde_json::from_str::<Exchange<'_, T>>(&contents)?.into_owned())
+    }
+}
+
+#[cfg(feature = "binary")]
+impl<T: serde::de::DeserializeOwned + 'static> Exchange<'static, T> {
+    /// Deserialize from `MessagePack` (see [`Exchange::to_bytes`]).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(rmp_serde::from_slice::<Exchange<'_, T>>(bytes)?.into_owned())
+    }
+}
+
+/// How to compress exchange files.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Compression {
+    #[default]
+    None,
+    /// Gzip with the given level (from 0 to 9).
+    Gzip { level: u32 },
+}
+
+impl Compression {
+    /// Gzip with the default level.
+    pub const GZIP: Self = Self::Gzip { level: 6 };
+}
+
+/// How exchange files are encoded.
+///
+/// This is non-exhaustive because some formats are only available with optional features.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Format {
+    /// JSON, possibly compressed.
+    Json(Compression),
+    /// `MessagePack` (see [`Exchange::to_bytes`]).
+    #[cfg(feature = "binary")]
+    MessagePack,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self::Json(Compression::None)
+    }
+}
+
+impl From<Compression> for Format {
+    fn from(compression: Compression) -> Self {
+        Self::Json(compression)
+    }
+}
+
+impl Format {
+    /// The format of the file at the given path, based on its extension.
+    #[must_use]
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gz") => Self::Json(Compression::GZIP),
+            #[cfg(feature = "binary")]
+            Some("bin") => Self::MessagePack,
+            _ => Self::Json(Compression::None),
+        }
+    }
+
+    /// The file name extension for exchange files in this format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json(Compression::None) => "json",
+            Self::Json(Compression::Gzip { .. }) => "json.gz",
+            #[cfg(feature = "binary")]
+            Self::MessagePack => "bin",
         }
     }
 }
 
+/// How to name exchange files (see [`Exchange::save_file_named`]).
+pub trait PathNaming {
+    /// The file name, without the extension (which depends on the [`Format`]).
+    fn file_stem<T>(&self, exchange: &Exchange<'_, T>) -> String;
+}
+
+/// Name files `{timestamp_ms}`, which is the default.
+///
+/// Exchanges with requests in the same millisecond get the same name.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TimestampNaming;
+
+impl PathNaming for TimestampNaming {
+    fn file_stem<T>(&self, exchange: &Exchange<'_, T>) -> String {
+        exchange.request.timestamp.timestamp_millis().to_string()
+    }
+}
+
+/// Name files `{timestamp_ms}-{url_hash}`, where the hash is a decimal number derived from the
+/// SHA-256 hash of the request URL.
+///
+/// Only requests for the same URL in the same millisecond get the same name, and the names still
+/// sort by timestamp in a store.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TimestampUrlHashNaming;
+
+impl PathNaming for TimestampUrlHashNaming {
+    fn file_stem<T>(&self, exchange: &Exchange<'_, T>) -> String {
+        use sha2::Digest;
+
+        let digest = sha2::Sha256::digest(exchange.request.url.as_str());
+        let mut hash = [0; 8];
+        hash.copy_from_slice(&digest[..8]);
+
+        format!(
+            "{}-{}",
+            exchange.request.timestamp.timestamp_millis(),
+            u64::from_be_bytes(hash)
+        )
+    }
+}
+
 impl<T: serde::ser::Serialize> Exchange<'_, T> {
     pub fn save_file<P: AsRef<Path>>(&self, base: P) -> Result<PathBuf, std::io::Error> {
+        self.save_file_with(base, Compression::None)
+    }
+
+    /// Save to a gzipped file named `<timestamp>.json.gz`, using the default compression level.
+    pub fn save_file_gz<P: AsRef<Path>>(&self, base: P) -> Result<PathBuf, std::io::Error> {
+        self.save_file_with(base, Compression::GZIP)
+    }
+
+    pub fn save_file_with<P: AsRef<Path>>(
+        &self,
+        base: P,
+        compression: Compression,
+    ) -> Result<PathBuf, std::io::Error> {
+        self.save_file_named(base, compression, &TimestampNaming, true)
+    }
+
+    /// Save to a file named by the given strategy.
+    ///
+    /// The file is written atomically: the contents are written to a temporary file in the same
+    /// directory, which is then moved into place, so readers never see a partially written file.
+    /// If `overwrite` is false and the file already exists, this fails with an error of kind
+    /// [`std::io::ErrorKind::AlreadyExists`].
+    pub fn save_file_named<P: AsRef<Path>, F: Into<Format>, N: PathNaming>(
+        &self,
+        base: P,
+        format: F,
+        naming: &N,
+        overwrite: bool,
+    ) -> Result<PathBuf, std::io::Error> {
+        let format = format.into();
+
         std::fs::create_dir_all(&base)?;
 
-        let output_path = base.as_ref().join(format!(
-            "{}.json",
-            self.request.timestamp.timestamp_millis()
-        ));
+        let output_path =
+            base.as_ref()
+                .join(format!("{}.{}", naming.file_stem(self), format.extension()));
 
         // We assume serialization failures are rare and don't need a separate error
         // representation.
         let json = serde_json::to_string(self).map_err(std::io::Error::other)?;
 
-        std::fs::write(&output_path, json)?;
+        write_file_atomic(&output_path, &json, format, overwrite)?;
 
         Ok(output_path)
     }
+
+    /// Save to a `MessagePack` file named `<timestamp>.bin`.
+    #[cfg(feature = "binary")]
+    pub fn save_file_bin<P: AsRef<Path>>(&self, base: P) -> Result<PathBuf, std::io::Error> {
+        self.save_file_named(base, Format::MessagePack, &TimestampNaming, true)
+    }
+
+    /// Serialize as `MessagePack`.
+    ///
+    /// Fields are encoded by name, since optional fields are omitted when empty (so formats like
+    /// `bincode` that rely on field order cannot represent exchanges).
+    #[cfg(feature = "binary")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        Ok(rmp_serde::to_vec_named(self)?)
+    }
+
+    /// Serialize as a single line of canonical JSON (with sorted keys), followed by a newline.
+    pub fn to_ndjson_line(&self) -> Result<String, Error> {
+        let mut line = crate::canonical::to_string(self)?;
+        line.push('\n');
+
+        Ok(line)
+    }
+}
+
+impl<'a> Exchange<'a, serde_json::Value> {
+    /// Convert the JSON response data into a strongly-typed value.
+    ///
+    /// On failure the error reports the path to the part of the JSON that did not match `T`.
+    pub fn parse_data<T: serde::de::DeserializeOwned>(
+        self,
+    ) -> Result<Exchange<'a, T>, serde_path_to_error::Error<serde_json::Error>> {
+        Ok(Exchange {
+            request: self.request,
+            response: self.response.and_then(serde_path_to_error::deserialize)?,
+            tags: self.tags,
+            duration_ms: self.duration_ms,
+        })
+    }
+}
+
+/// Read an exchange file as JSON, decompressing it if its name ends in `.gz`.
+///
+/// `MessagePack` (`.bin`) files are converted to JSON.
+pub(crate) fn read_file(path: &Path) -> Result<String, std::io::Error> {
+    match Format::for_path(path) {
+        Format::Json(Compression::None) => std::fs::read_to_string(path),
+        Format::Json(Compression::Gzip { .. }) => {
+            use std::io::Read;
+
+            let mut contents = String::new();
+            flate2::read::MultiGzDecoder::new(std::fs::File::open(path)?)
+                .read_to_string(&mut contents)?;
+
+            Ok(contents)
+        }
+        #[cfg(feature = "binary")]
+        Format::MessagePack => {
+            let value = rmp_serde::from_slice::<serde_json::Value>(&std::fs::read(path)?)
+                .map_err(std::io::Error::other)?;
+
+            serde_json::to_string(&value).map_err(std::io::Error::other)
+        }
+    }
+}
+
+/// Write JSON exchange contents to a file atomically (replacing any existing file), compressing or
+/// encoding them as specified.
+pub(crate) fn write_file(
+    path: &Path,
+    contents: &str,
+    format: Format,
+) -> Result<(), std::io::Error> {
+    write_file_atomic(path, contents, format, true)
+}
+
+/// The extension for temporary files, which are never treated as exchange files.
+pub(crate) const TEMP_EXTENSION: &str = "tmp";
+
+/// Write to a temporary file in the same directory, and then move it into place.
+///
+/// If `overwrite` is false, the temporary file is hard-linked to the target, which fails if the
+/// target already exists.
+pub(crate) fn write_file_atomic(
+    path: &Path,
+    contents: &str,
+    format: Format,
+    overwrite: bool,
+) -> Result<(), std::io::Error> {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+    let file_name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "missing file name")
+    })?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}-{}.{TEMP_EXTENSION}",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    ));
+
+    let result = write_file_contents(&temp_path, contents, format).and_then(|()| {
+        if overwrite {
+            std::fs::rename(&temp_path, path)
+        } else {
+            std::fs::hard_link(&temp_path, path).and_then(|()| std::fs::remove_file(&temp_path))
+        }
+    });
+
+    if result.is_err() {
+        // The temporary file may not exist, and the original error is more useful.
+        let _ = std::fs::remove_file(&temp_path);
+    }
+
+    result
+}
+
+fn write_file_contents(path: &Path, contents: &str, format: Format) -> Result<(), std::io::Error> {
+    use std::io::Write;
+
+    let mut file = std::fs::File::create(path)?;
+
+    match format {
+        Format::Json(Compression::None) => file.write_all(contents.as_bytes())?,
+        Format::Json(Compression::Gzip { level }) => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(&mut file, flate2::Compression::new(level));
+            encoder.write_all(contents.as_bytes())?;
+            encoder.finish()?;
+        }
+        #[cfg(feature = "binary")]
+        Format::MessagePack => {
+            let value = serde_json::from_str::<serde_json::Value>(contents)
+                .map_err(std::io::Error::other)?;
+
+            file.write_all(&rmp_serde::to_vec_named(&value).map_err(std::io::Error::other)?)?;
+        }
+    }
+
+    file.sync_all()
+}
+
+const TRUNCATION_MARKER: &str = "…(truncated)";
+
+impl Exchange<'_, serde_json::Value> {
+    /// Truncate every string in the response data that is longer than `max_len` characters.
+    ///
+    /// Truncated strings keep their first `max_len` characters followed by a marker.
+    pub fn truncate_strings(&mut self, max_len: usize) {
+        let mut stack = vec![&mut self.response.data];
+
+        while let Some(value) = stack.pop() {
+            match value {
+                serde_json::Value::String(string) => {
+                    if let Some((index, _)) = string.char_indices().nth(max_len) {
+                        string.truncate(index);
+                        string.push_str(TRUNCATION_MARKER);
+                    }
+                }
+                serde_json::Value::Array(values) => stack.extend(values.iter_mut()),
+                serde_json::Value::Object(fields) => stack.extend(fields.values_mut()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Truncate every array in the response data to at most `max_items` elements.
+    pub fn truncate_arrays(&mut self, max_items: usize) {
+        let mut stack = vec![&mut self.response.data];
+
+        while let Some(value) = stack.pop() {
+            match value {
+                serde_json::Value::Array(values) => {
+                    values.truncate(max_items);
+                    stack.extend(values.iter_mut());
+                }
+                serde_json::Value::Object(fields) => stack.extend(fields.values_mut()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Convert into a JSON value, moving the response data into it instead of copying it.
+    pub fn into_json_value(mut self) -> Result<serde_json::Value, serde_json::Error> {
+        let data = std::mem::take(&mut self.response.data);
+        let mut value = serde_json::to_value(&self)?;
+
+        if let Some(response) = value.get_mut("response") {
+            response["data"] = data;
+        }
+
+        Ok(value)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[non_exhaustive]
 pub struct Response<'a, T> {
-    #[serde(borrow)]
+    #[serde(borrow, default, skip_serializing_if = "HashMap::is_empty")]
     pub headers: HashMap<Cow<'a, str>, MultiValue<'a>>,
     pub data: T,
+    /// The response status, if it was recorded (archives created by earlier versions do not
+    /// include it).
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "status_code")]
+    pub status: Option<StatusCode>,
+    /// The HTTP version used for the exchange, if it was recorded.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "http_version"
+    )]
+    pub http_version: Option<Version>,
+    /// The exact response body, if it was captured alongside the parsed data.
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Cow<'a, str>>,
+    /// Trailing headers sent after the body (e.g. by gRPC-Web).
+    ///
+    /// The client functions do not capture trailers, since `reqwest` does not expose them through
+    /// its response API, but archives that include them are supported.
+    #[serde(borrow, default, skip_serializing_if = "HashMap::is_empty")]
+    pub trailers: HashMap<Cow<'a, str>, MultiValue<'a>>,
 }
 
 impl<'a, T> Response<'a, T> {
+    pub fn new(headers: HashMap<Cow<'a, str>, MultiValue<'a>>, data: T) -> Self {
+        Self {
+            headers,
+            data,
+            status: None,
+            http_version: None,
+            raw: None,
+            trailers: HashMap::new(),
+        }
+    }
+
+    pub fn with_status(
+        headers: HashMap<Cow<'a, str>, MultiValue<'a>>,
+        data: T,
+        status: StatusCode,
+    ) -> Self {
+        Self {
+            headers,
+            data,
+            status: Some(status),
+            http_version: None,
+            raw: None,
+            trailers: HashMap::new(),
+        }
+    }
+
     pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Response<'a, U> {
         Response {
             headers: self.headers,
             data: f(self.data),
+            status: self.status,
+            http_version: self.http_version,
+            raw: self.raw,
+            trailers: self.trailers,
+        }
+    }
+
+    /// A copy of the response metadata (headers, status, etc.) with different data.
+    pub(crate) fn with_data<U>(&self, data: U) -> Response<'a, U> {
+        Response {
+            headers: self.headers.clone(),
+            data,
+            status: self.status,
+            http_version: self.http_version,
+            raw: self.raw.clone(),
+            trailers: self.trailers.clone(),
         }
     }
 
@@ -90,8 +579,392 @@ impl<'a, T> Response<'a, T> {
         f(self.data).map(|new_data| Response {
             headers: self.headers,
             data: new_data,
+            status: self.status,
+            http_version: self.http_version,
+            raw: self.raw,
+            trailers: self.trailers,
+        })
+    }
+}
+
+impl<T> Response<'_, T> {
+    /// The length in bytes of the longest header value.
+    #[must_use]
+    pub fn header_value_max_len(&self) -> usize {
+        self.headers
+            .values()
+            .flat_map(|values| values.iter().map(|value| value.len()))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Replace every value of the named headers (compared case-insensitively) with
+    /// [`crate::request::redact::REDACTED`], keeping the number of values.
+    pub fn redact(&mut self, names: &[&str]) {
+        for (name, values) in &mut self.headers {
+            if crate::request::redact::matches_name(names, name) {
+                for value in values.iter_mut() {
+                    *value = Cow::Borrowed(crate::request::redact::REDACTED);
+                }
+            }
+        }
+    }
+
+    /// Truncate every header value to at most `max` bytes (at a character boundary).
+    pub fn truncate_header_values(&mut self, max: usize) {
+        for values in self.headers.values_mut() {
+            for value in values.iter_mut() {
+                if value.len() > max {
+                    let index = value.floor_char_boundary(max);
+
+                    value.to_mut().truncate(index);
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T> Response<'a, T> {
+    /// Look up the values for a header by name (compared case-insensitively).
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&MultiValue<'a>> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, values)| values)
+    }
+
+    /// Parse the `Link` header (see RFC 8288) into a map from relation types to URLs.
+    ///
+    /// Links with multiple relation types appear under each of them, and if a relation type
+    /// appears more than once, the first link is used. Links whose targets are not absolute URLs
+    /// are ignored.
+    #[must_use]
+    pub fn links(&self) -> HashMap<String, url::Url> {
+        let mut links = HashMap::new();
+
+        for value in self.header("link").into_iter().flat_map(MultiValue::iter) {
+            for link in crate::header::split_list(&value) {
+                let Some((target, params)) =
+                    link.strip_prefix('<').and_then(|link| link.split_once('>'))
+                else {
+                    continue;
+                };
+
+                let Ok(url) = target.trim().parse::<url::Url>() else {
+                    continue;
+                };
+
+                for (name, value) in crate::header::parse_params(params) {
+                    if name == "rel" {
+                        for rel in value.split_ascii_whitespace() {
+                            links
+                                .entry(rel.to_ascii_lowercase())
+                                .or_insert_with(|| url.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        links
+    }
+
+    /// The URL of the `next` link from the `Link` header, if there is one.
+    #[must_use]
+    pub fn next_link(&self) -> Option<url::Url> {
+        self.links().remove("next")
+    }
+
+    /// Parse the `Content-Range` header (see RFC 9110, section 14.4).
+    ///
+    /// Only byte ranges are supported, and unsatisfied ranges (e.g. `bytes */4096`) are treated as
+    /// missing.
+    #[must_use]
+    pub fn content_range(&self) -> Option<ContentRange> {
+        let value = self.header("content-range")?;
+        let (unit, range) = value.first.trim().split_once(' ')?;
+
+        if !unit.eq_ignore_ascii_case("bytes") {
+            return None;
+        }
+
+        let (range, total) = range.trim().split_once('/')?;
+        let (start, end) = range.split_once('-')?;
+
+        Some(ContentRange {
+            start: start.parse().ok()?,
+            end: end.parse().ok()?,
+            total: if total == "*" {
+                None
+            } else {
+                Some(total.parse().ok()?)
+            },
         })
     }
+
+    /// Whether the response has the `206 Partial Content` status.
+    #[must_use]
+    pub fn is_partial(&self) -> bool {
+        self.status == Some(http::StatusCode::PARTIAL_CONTENT)
+    }
+
+    /// Parse the cookies set by the `Set-Cookie` headers, skipping invalid values.
+    #[must_use]
+    pub fn set_cookies(&self) -> Vec<crate::cookie::Cookie> {
+        self.header("set-cookie")
+            .into_iter()
+            .flat_map(MultiValue::iter)
+            .filter_map(|value| crate::cookie::Cookie::parse_set_cookie(&value))
+            .collect()
+    }
+
+    /// Parse the `WWW-Authenticate` header (see RFC 7235) into its challenges.
+    ///
+    /// Parameter names are lowercased and quoted values are unquoted. Challenges using the
+    /// `token68` syntax (instead of parameters) are returned without parameters.
+    #[must_use]
+    pub fn auth_challenges(&self) -> Vec<AuthChallenge> {
+        let mut challenges: Vec<AuthChallenge> = vec![];
+
+        for value in self
+            .header("www-authenticate")
+            .into_iter()
+            .flat_map(MultiValue::iter)
+        {
+            for part in crate::header::split_list(&value) {
+                let (param, starts_challenge) = match part.split_once(char::is_whitespace) {
+                    Some((scheme, rest)) if !scheme.contains('=') => {
+                        challenges.push(AuthChallenge::new(scheme));
+                        (rest.trim(), true)
+                    }
+                    None if !part.contains('=') => {
+                        challenges.push(AuthChallenge::new(part));
+                        continue;
+                    }
+                    _ => (part, false),
+                };
+
+                // A `token68` may end with `=` padding but contains no other `=`.
+                if starts_challenge && !param.trim_end_matches('=').contains('=') {
+                    continue;
+                }
+
+                if let (Some(challenge), Some((name, value))) =
+                    (challenges.last_mut(), param.split_once('='))
+                {
+                    challenge.params.insert(
+                        name.trim().to_ascii_lowercase(),
+                        crate::header::unquote(value.trim()),
+                    );
+                }
+            }
+        }
+
+        challenges
+    }
+}
+
+/// A byte range from a `Content-Range` header.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ContentRange {
+    pub start: u64,
+    /// The last byte position (inclusive).
+    pub end: u64,
+    /// The complete length, if known.
+    pub total: Option<u64>,
+}
+
+/// An authentication challenge from a `WWW-Authenticate` header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuthChallenge {
+    pub scheme: String,
+    pub params: HashMap<String, String>,
+}
+
+impl AuthChallenge {
+    fn new(scheme: &str) -> Self {
+        Self {
+            scheme: scheme.to_string(),
+            params: HashMap::new(),
+        }
+    }
+}
+
+impl<T: serde::ser::Serialize> Response<'_, T> {
+    /// Serialize to a JSON value in which every header value is an array (even singletons).
+    pub fn to_json_with_array_headers(&self) -> Result<serde_json::Value, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+
+        if let Some(headers) = value.get_mut("headers") {
+            *headers = crate::multi_value::serialize_map_as_arrays(
+                &self.headers,
+                serde_json::value::Serializer,
+            )?;
+        }
+
+        Ok(value)
+    }
+}
+
+impl Response<'_, String> {
+    /// Check whether the body appears to be JSON, regardless of the declared content type.
+    #[must_use]
+    pub fn looks_like_json(&self) -> bool {
+        let trimmed = self.data.trim_start();
+
+        (trimmed.starts_with('{') || trimmed.starts_with('['))
+            && serde_json::from_str::<serde::de::IgnoredAny>(trimmed).is_ok()
+    }
+
+    /// Check whether the body appears to be an HTML document, regardless of the declared content
+    /// type.
+    #[must_use]
+    pub fn looks_like_html(&self) -> bool {
+        let trimmed = self.data.trim_start().as_bytes();
+        let prefix = &trimmed[..trimmed.len().min(9)];
+
+        prefix.eq_ignore_ascii_case(b"<!doctype")
+            || prefix
+                .get(..5)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(b"<html"))
+    }
+}
+
+impl Response<'_, Vec<u8>> {
+    /// Decode a body that was stored with its `Content-Encoding` still applied.
+    ///
+    /// Supports `gzip`, `deflate`, and `br` (and `identity`), undoing multiple encodings in
+    /// reverse order. The `Content-Encoding` header is removed on success, and the body is left
+    /// unchanged on failure.
+    pub fn decompress_body(&mut self) -> Result<(), Error> {
+        fn read_all<R: std::io::Read>(mut reader: R) -> Result<Vec<u8>, Error> {
+            let mut data = vec![];
+            reader.read_to_end(&mut data)?;
+
+            Ok(data)
+        }
+
+        let Some(key) = self
+            .headers
+            .keys()
+            .find(|key| key.eq_ignore_ascii_case("content-encoding"))
+            .cloned()
+        else {
+            return Ok(());
+        };
+
+        let encodings = self.headers[&key]
+            .iter()
+            .flat_map(|value| {
+                crate::header::split_list(&value)
+                    .into_iter()
+                    .map(str::to_ascii_lowercase)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // The original data is only replaced once every encoding has been undone.
+        let mut decoded: Option<Vec<u8>> = None;
+
+        for encoding in encodings.iter().rev() {
+            let input = decoded.as_deref().unwrap_or(&self.data);
+
+            decoded = Some(match encoding.as_str() {
+                "gzip" | "x-gzip" => read_all(flate2::read::MultiGzDecoder::new(input))?,
+                // Servers often send raw deflate data instead of the zlib format the spec requires.
+                "deflate" => read_all(flate2::read::ZlibDecoder::new(input))
+                    .or_else(|_| read_all(flate2::read::DeflateDecoder::new(input)))?,
+                "br" => read_all(brotli::Decompressor::new(input, 4096))?,
+                "identity" => continue,
+                other => return Err(Error::UnsupportedContentEncoding(other.to_string())),
+            });
+        }
+
+        if let Some(decoded) = decoded {
+            self.data = decoded;
+        }
+
+        self.headers.remove(&key);
+
+        Ok(())
+    }
+}
+
+impl Response<'_, serde_json::Value> {
+    /// A weak `ETag` computed from the SHA-256 hash of the canonical JSON representation of the
+    /// data, for change detection when the origin does not provide one.
+    #[must_use]
+    pub fn synthetic_etag(&self) -> String {
+        use sha2::Digest;
+
+        let mut data = self.data.clone();
+        crate::canonical::sort_keys(&mut data);
+
+        let digest = sha2::Sha256::digest(data.to_string());
+
+        format!("W/\"{}\"", crate::hex::encode(&digest))
+    }
+
+    /// Parse a string found at the given JSON pointer as JSON.
+    ///
+    /// Some APIs (e.g. Google's `batchexecute`) return payloads as JSON-encoded strings inside a
+    /// JSON envelope. Returns `None` if there is no string at the pointer, or if it is not valid
+    /// JSON.
+    #[must_use]
+    pub fn parse_nested_json(&self, ptr: &str) -> Option<serde_json::Value> {
+        self.data
+            .pointer(ptr)
+            .and_then(serde_json::Value::as_str)
+            .and_then(|value| serde_json::from_str(value).ok())
+    }
+
+    /// The maximum nesting depth of arrays and objects in the data.
+    ///
+    /// Scalars have depth 0, and each enclosing array or object (even an empty one) adds 1. This
+    /// is computed iteratively, so it is safe to call on arbitrarily deep values.
+    #[must_use]
+    pub fn max_depth(&self) -> usize {
+        let mut stack = vec![(&self.data, 0)];
+        let mut max_depth = 0;
+
+        while let Some((value, depth)) = stack.pop() {
+            let children: Box<dyn Iterator<Item = &serde_json::Value>> = match value {
+                serde_json::Value::Array(values) => Box::new(values.iter()),
+                serde_json::Value::Object(fields) => Box::new(fields.values()),
+                _ => continue,
+            };
+
+            max_depth = max_depth.max(depth + 1);
+            stack.extend(children.map(|child| (child, depth + 1)));
+        }
+
+        max_depth
+    }
+}
+
+impl<T: 'static> Response<'_, T> {
+    /// Convert into a `'static` response for data types that are already owned (such as
+    /// `serde_json::Value`) but do not implement `IntoBoundedStatic`.
+    #[must_use]
+    pub fn into_owned(self) -> Response<'static, T> {
+        Response {
+            headers: self
+                .headers
+                .into_iter()
+                .map(|(key, values)| (key.into_static(), values.into_static()))
+                .collect(),
+            data: self.data,
+            status: self.status,
+            http_version: self.http_version,
+            raw: self.raw.into_static(),
+            trailers: self
+                .trailers
+                .into_iter()
+                .map(|(key, values)| (key.into_static(), values.into_static()))
+                .collect(),
+        }
+    }
 }
 
 impl<'a, T: IntoBoundedStatic + 'a> IntoBoundedStatic for Response<'a, T> {
@@ -105,6 +978,14 @@ impl<'a, T: IntoBoundedStatic + 'a> IntoBoundedStatic for Response<'a, T> {
                 .map(|(key, values)| (key.into_static(), values.into_static()))
                 .collect(),
             data: self.data.into_static(),
+            status: self.status,
+            http_version: self.http_version,
+            raw: self.raw.into_static(),
+            trailers: self
+                .trailers
+                .into_iter()
+                .map(|(key, values)| (key.into_static(), values.into_static()))
+                .collect(),
         }
     }
 }
@@ -120,18 +1001,101 @@ impl<T: ToBoundedStatic> ToBoundedStatic for Response<'_, T> {
                 .map(|(key, values)| (key.to_static(), values.to_static()))
                 .collect(),
             data: self.data.to_static(),
+            status: self.status,
+            http_version: self.http_version,
+            raw: self.raw.to_static(),
+            trailers: self
+                .trailers
+                .iter()
+                .map(|(key, values)| (key.to_static(), values.to_static()))
+                .collect(),
+        }
+    }
+}
+
+mod status_code {
+    use http::StatusCode;
+    use serde::de::{Deserialize, Deserializer, Error};
+    use serde::ser::Serializer;
+
+    #[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(
+        value: &Option<StatusCode>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(status) => serializer.serialize_some(&status.as_u16()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<StatusCode>, D::Error> {
+        Option::<u16>::deserialize(deserializer)?
+            .map(|code| StatusCode::from_u16(code).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+pub(crate) mod http_version {
+    use http::Version;
+    use serde::de::{Deserialize, Deserializer, Error};
+    use serde::ser::Serializer;
+
+    const VERSIONS: [(Version, &str); 5] = [
+        (Version::HTTP_09, "HTTP/0.9"),
+        (Version::HTTP_10, "HTTP/1.0"),
+        (Version::HTTP_11, "HTTP/1.1"),
+        (Version::HTTP_2, "HTTP/2.0"),
+        (Version::HTTP_3, "HTTP/3.0"),
+    ];
+
+    /// The archived name of an HTTP version (e.g. `HTTP/1.1`).
+    pub fn name(version: Version) -> Option<&'static str> {
+        VERSIONS
+            .iter()
+            .find(|(known, _)| *known == version)
+            .map(|(_, name)| *name)
+    }
+
+    #[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(
+        value: &Option<Version>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(version) => serializer.serialize_some(name(*version).ok_or_else(|| {
+                serde::ser::Error::custom(format!("unknown HTTP version: {version:?}"))
+            })?),
+            None => serializer.serialize_none(),
         }
     }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Version>, D::Error> {
+        Option::<std::borrow::Cow<'de, str>>::deserialize(deserializer)?
+            .map(|value| {
+                VERSIONS
+                    .iter()
+                    .find(|(_, name)| *name == value)
+                    .map(|(version, _)| *version)
+                    .ok_or_else(|| D::Error::custom(format!("unknown HTTP version: {value}")))
+            })
+            .transpose()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Exchange;
+    use super::{Exchange, Response};
 
     const APPLE_ITUNES_01_EXAMPLE: &str = include_str!("../../examples/apple-itunes-01.json");
     const GOOGLE_PLAY_01_EXAMPLE: &str = include_str!("../../examples/google-play-01.json");
 
     #[test]
+    #[allow(clippy::unreadable_literal)]
     fn deserialize_example_apple_itunes_01() -> Result<(), Box<dyn std::error::Error>> {
         let example: Exchange<'_, serde_json::Value> =
             serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
@@ -153,6 +1117,37 @@ mod tests {
     }
 
     #[test]
+    fn http_version_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let mut exchange: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+
+        for version in [
+            http::Version::HTTP_09,
+            http::Version::HTTP_10,
+            http::Version::HTTP_11,
+            http::Version::HTTP_2,
+            http::Version::HTTP_3,
+        ] {
+            exchange.response.http_version = Some(version);
+
+            let json = serde_json::to_string(&exchange)?;
+
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(&json)?["response"]["http_version"]
+                    .as_str(),
+                super::http_version::name(version)
+            );
+            assert_eq!(
+                serde_json::from_str::<Exchange<'_, serde_json::Value>>(&json)?,
+                exchange
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::unreadable_literal)]
     fn deserialize_example_google_play_01() -> Result<(), Box<dyn std::error::Error>> {
         let example: Exchange<'_, serde_json::Value> =
             serde_json::from_str(GOOGLE_PLAY_01_EXAMPLE)?;
@@ -172,4 +1167,559 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn truncate_data() -> Result<(), Box<dyn std::error::Error>> {
+        let mut example: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+
+        example.response.data = serde_json::json!({
+            "short": "abc",
+            "long": "abcdéfgh",
+            "items": [[1, 2, 3], 4, 5, "abcdefgh"]
+        });
+
+        example.truncate_strings(5);
+        example.truncate_arrays(2);
+
+        assert_eq!(
+            example.response.data,
+            serde_json::json!({
+                "short": "abc",
+                "long": "abcdé…(truncated)",
+                "items": [[1, 2], 4]
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sniff_body() {
+        let response = |data: &str| Response {
+            headers: std::collections::HashMap::new(),
+            data: data.to_string(),
+            status: None,
+            http_version: None,
+            raw: None,
+            trailers: std::collections::HashMap::new(),
+        };
+
+        assert!(response(" {\"a\": [1, 2]}").looks_like_json());
+        assert!(!response("{not json").looks_like_json());
+        assert!(!response("<!DOCTYPE html><html></html>").looks_like_json());
+        assert!(response("\n<!DOCTYPE html><html></html>").looks_like_html());
+        assert!(response("<HTML><body></body></HTML>").looks_like_html());
+        assert!(!response("[]").looks_like_html());
+    }
+
+    #[test]
+    fn parse_data_error_path() -> Result<(), Box<dyn std::error::Error>> {
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Lookup {
+            results: Vec<App>,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct App {
+            #[serde(rename = "artistName")]
+            artist_name: u64,
+        }
+
+        let example: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+
+        let error = example.parse_data::<Lookup>().unwrap_err();
+
+        assert_eq!(error.path().to_string(), "results[0].artistName");
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nested_json() {
+        let response = Response {
+            headers: std::collections::HashMap::new(),
+            data: serde_json::json!([["wrb.fr", "UsvDTd", "[[1,\"a\"],null]", null]]),
+            status: None,
+            http_version: None,
+            raw: None,
+            trailers: std::collections::HashMap::new(),
+        };
+
+        assert_eq!(
+            response.parse_nested_json("/0/2"),
+            Some(serde_json::json!([[1, "a"], null]))
+        );
+        assert_eq!(response.parse_nested_json("/0/1"), None);
+        assert_eq!(response.parse_nested_json("/0/3"), None);
+    }
+
+    #[test]
+    fn to_ndjson_line() -> Result<(), Box<dyn std::error::Error>> {
+        let example: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+
+        let line = example.to_ndjson_line()?;
+
+        assert!(line.ends_with('\n'));
+        assert_eq!(line.lines().count(), 1);
+        assert!(line.starts_with(r#"{"request":{"timestamp_ms":"1760252742866","url":"#));
+        assert_eq!(
+            serde_json::from_str::<Exchange<'_, serde_json::Value>>(&line)?,
+            example
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn links() -> Result<(), Box<dyn std::error::Error>> {
+        let response = Response::new(
+            std::collections::HashMap::from([(
+                "Link".into(),
+                crate::multi_value::MultiValue::new(
+                    r#"<https://api.example.com/items?page=2>; rel="next", <https://api.example.com/items?page=5>; rel="last alternate", </relative>; rel=prev"#,
+                ),
+            )]),
+            (),
+        );
+
+        let links = response.links();
+
+        assert_eq!(links.len(), 3);
+        assert_eq!(
+            links["last"].as_str(),
+            "https://api.example.com/items?page=5"
+        );
+        assert_eq!(links["alternate"], links["last"]);
+        assert_eq!(
+            response.next_link(),
+            Some("https://api.example.com/items?page=2".parse()?)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn synthetic_etag() {
+        let response = |data| Response::new(std::collections::HashMap::new(), data);
+
+        let etag = response(serde_json::json!({"a": 1, "b": [true]})).synthetic_etag();
+
+        assert!(etag.starts_with("W/\"") && etag.ends_with('"'));
+        assert_eq!(etag.len(), 64 + 4);
+        assert_eq!(
+            etag,
+            response(serde_json::json!({"b": [true], "a": 1})).synthetic_etag()
+        );
+        assert_ne!(
+            etag,
+            response(serde_json::json!({"a": 2, "b": [true]})).synthetic_etag()
+        );
+    }
+
+    #[test]
+    fn to_json_with_array_headers() -> Result<(), Box<dyn std::error::Error>> {
+        let mut response = Response::new(std::collections::HashMap::new(), 1);
+
+        assert_eq!(
+            response.to_json_with_array_headers()?,
+            serde_json::json!({"data": 1})
+        );
+
+        response.headers.insert(
+            "content-type".into(),
+            crate::multi_value::MultiValue::new("text/plain"),
+        );
+        response
+            .headers
+            .insert("set-cookie".into(), vec!["a=1", "b=2"].try_into()?);
+
+        assert_eq!(
+            response.to_json_with_array_headers()?,
+            serde_json::json!({
+                "headers": {"content-type": ["text/plain"], "set-cookie": ["a=1", "b=2"]},
+                "data": 1
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_empty_headers() -> Result<(), Box<dyn std::error::Error>> {
+        let response = Response::new(std::collections::HashMap::new(), 1);
+        let json = serde_json::to_string(&response)?;
+
+        assert_eq!(json, r#"{"data":1}"#);
+        assert_eq!(serde_json::from_str::<Response<'_, i32>>(&json)?, response);
+
+        Ok(())
+    }
+
+    #[test]
+    fn trailers() -> Result<(), Box<dyn std::error::Error>> {
+        let json = r#"{"headers":{"content-type":"application/grpc-web+proto"},"data":null,"trailers":{"grpc-status":"0","grpc-message":["a","b"]}}"#;
+        let response: Response<'_, ()> = serde_json::from_str(json)?;
+
+        assert_eq!(response.trailers.len(), 2);
+        assert_eq!(
+            response
+                .trailers
+                .get("grpc-message")
+                .map(crate::multi_value::MultiValue::len),
+            Some(2)
+        );
+        assert_eq!(
+            serde_json::from_str::<Response<'_, ()>>(&serde_json::to_string(&response)?)?,
+            response
+        );
+        assert!(
+            !serde_json::to_string(&Response::new(std::collections::HashMap::new(), ()))?
+                .contains("trailers")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn redact() -> Result<(), Box<dyn std::error::Error>> {
+        let mut values = crate::multi_value::MultiValue::new("a=1");
+        values.push("b=2");
+
+        let mut headers = std::collections::HashMap::new();
+        headers.insert("Set-Cookie".into(), values);
+        headers.insert("server".into(), crate::multi_value::MultiValue::new("test"));
+
+        let mut response = Response::new(headers, ());
+        response.redact(&["set-cookie"]);
+
+        let redacted = response.header("set-cookie").ok_or("missing header")?;
+
+        assert_eq!(redacted.len(), 2);
+        assert!(
+            redacted
+                .iter()
+                .all(|value| value == crate::request::redact::REDACTED)
+        );
+        assert_eq!(
+            response
+                .header("server")
+                .map(|values| values.first.as_ref()),
+            Some("test")
+        );
+
+        let json = serde_json::to_string(&response)?;
+
+        assert!(!json.contains("a=1"));
+        assert_eq!(serde_json::from_str::<Response<'_, ()>>(&json)?, response);
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_depth() {
+        let depth = |data| Response::new(std::collections::HashMap::new(), data).max_depth();
+
+        assert_eq!(depth(serde_json::json!(1)), 0);
+        assert_eq!(depth(serde_json::json!([])), 1);
+        assert_eq!(
+            depth(serde_json::json!({"a": [1, {"b": [[]]}], "c": {}})),
+            5
+        );
+
+        // Deeper than `serde_json`'s recursion limit, so built without parsing.
+        let mut deep = serde_json::Value::Null;
+
+        for _ in 0..10_000 {
+            deep = serde_json::Value::Array(vec![deep]);
+        }
+
+        let response = Response::new(std::collections::HashMap::new(), deep);
+
+        assert_eq!(response.max_depth(), 10_000);
+
+        // Avoid a recursive drop of the deep value.
+        let mut stack = vec![response.data];
+
+        while let Some(value) = stack.pop() {
+            if let serde_json::Value::Array(values) = value {
+                stack.extend(values);
+            }
+        }
+    }
+
+    #[test]
+    fn decompress_body() -> Result<(), Box<dyn std::error::Error>> {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(br#"{"a":1}"#)?;
+
+        let mut headers = std::collections::HashMap::new();
+        headers.insert(
+            "Content-Encoding".into(),
+            crate::multi_value::MultiValue::new("gzip"),
+        );
+
+        let mut response = Response::new(headers, encoder.finish()?);
+        response.decompress_body()?;
+
+        assert_eq!(response.data, br#"{"a":1}"#);
+        assert!(response.header("content-encoding").is_none());
+
+        let mut headers = std::collections::HashMap::new();
+        headers.insert(
+            "content-encoding".into(),
+            crate::multi_value::MultiValue::new("zstd"),
+        );
+
+        let mut response = Response::new(headers, b"abc".to_vec());
+
+        assert!(response.decompress_body().is_err());
+        assert_eq!(response.data, b"abc");
+
+        // Raw deflate data is accepted for `deflate`.
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(b"raw")?;
+
+        let mut headers = std::collections::HashMap::new();
+        headers.insert(
+            "Content-Encoding".into(),
+            crate::multi_value::MultiValue::new("deflate"),
+        );
+
+        let mut response = Response::new(headers, encoder.finish()?);
+        response.decompress_body()?;
+
+        assert_eq!(response.data, b"raw");
+
+        // If a later step fails, the original data is kept (not the partially decoded data).
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(b"not deflate data")?;
+        let original = encoder.finish()?;
+
+        let mut headers = std::collections::HashMap::new();
+        headers.insert(
+            "Content-Encoding".into(),
+            crate::multi_value::MultiValue::new("deflate, gzip"),
+        );
+
+        let mut response = Response::new(headers, original.clone());
+
+        assert!(response.decompress_body().is_err());
+        assert_eq!(response.data, original);
+        assert!(response.header("content-encoding").is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn auth_challenges() {
+        let mut headers = std::collections::HashMap::new();
+        headers.insert(
+            "WWW-Authenticate".into(),
+            crate::multi_value::MultiValue::new(
+                r#"Bearer realm="api, v2", error="invalid_token", Basic, Negotiate abc123=="#,
+            ),
+        );
+        let response = Response::new(headers, ());
+        let challenges = response.auth_challenges();
+
+        assert_eq!(challenges.len(), 3);
+        assert_eq!(challenges[0].scheme, "Bearer");
+        assert_eq!(challenges[0].params["realm"], "api, v2");
+        assert_eq!(challenges[0].params["error"], "invalid_token");
+        assert_eq!(challenges[1].scheme, "Basic");
+        assert!(challenges[1].params.is_empty());
+        assert_eq!(challenges[2].scheme, "Negotiate");
+        assert!(challenges[2].params.is_empty());
+    }
+
+    #[test]
+    fn duration() -> Result<(), Box<dyn std::error::Error>> {
+        let mut example: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+
+        assert_eq!(example.duration(), None);
+        assert!(!serde_json::to_string(&example)?.contains("duration_ms"));
+
+        example.duration_ms = Some(1500);
+        let json = serde_json::to_string(&example)?;
+        let parsed: Exchange<'_, serde_json::Value> = serde_json::from_str(&json)?;
+
+        assert_eq!(
+            parsed.duration(),
+            Some(std::time::Duration::from_millis(1500))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_file() -> Result<(), Box<dyn std::error::Error>> {
+        let example: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+        let base = std::env::temp_dir().join(format!(
+            "scraper-trail-test-load-file-{}",
+            std::process::id()
+        ));
+
+        let path = example.save_file(&base)?;
+        let loaded = Exchange::<serde_json::Value>::load_file(&path)?;
+
+        assert_eq!(loaded, example);
+        assert!(matches!(
+            Exchange::<serde_json::Value>::load_file(base.join("missing.json")),
+            Err(super::Error::Io(_))
+        ));
+
+        std::fs::remove_dir_all(&base)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn save_file_named() -> Result<(), Box<dyn std::error::Error>> {
+        use super::{Compression, TimestampNaming, TimestampUrlHashNaming};
+
+        let mut example: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+        let base = std::env::temp_dir().join(format!(
+            "scraper-trail-test-save-file-named-{}",
+            std::process::id()
+        ));
+
+        let first = example.save_file_named(&base, Compression::None, &TimestampNaming, false)?;
+
+        assert_eq!(first, example.save_file(&base)?);
+        assert_eq!(
+            example
+                .save_file_named(&base, Compression::None, &TimestampNaming, false)
+                .map_err(|error| error.kind()),
+            Err(std::io::ErrorKind::AlreadyExists)
+        );
+
+        let second =
+            example.save_file_named(&base, Compression::None, &TimestampUrlHashNaming, false)?;
+        example.request.url.set_query(Some("id=2"));
+        let third =
+            example.save_file_named(&base, Compression::GZIP, &TimestampUrlHashNaming, false)?;
+
+        assert_ne!(second, third);
+        assert!(third.to_string_lossy().ends_with(".json.gz"));
+
+        let paths = crate::archive::store::Store::new(&base).paths(false)?;
+
+        // The files with hashes sort after the one without, in an order that depends on the hashes.
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[0], first);
+        assert!(paths.contains(&second) && paths.contains(&third));
+
+        std::fs::remove_dir_all(&base)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn save_file_atomic() -> Result<(), Box<dyn std::error::Error>> {
+        let example: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+        let base = std::env::temp_dir().join(format!(
+            "scraper-trail-test-save-file-atomic-{}",
+            std::process::id()
+        ));
+
+        let path = example.save_file(&base)?;
+        // Overwriting replaces the file, and a failed write leaves no temporary file behind.
+        example.save_file(&base)?;
+        assert!(
+            example
+                .save_file_named(
+                    &base,
+                    super::Compression::None,
+                    &super::TimestampNaming,
+                    false
+                )
+                .is_err()
+        );
+
+        assert_eq!(std::fs::read_dir(&base)?.count(), 1);
+        assert_eq!(Exchange::<serde_json::Value>::load_file(&path)?, example);
+
+        // A temporary file left by an interrupted write is not an exchange file.
+        std::fs::write(base.join(".1.json.1-0.tmp"), "{")?;
+
+        assert_eq!(
+            crate::archive::store::Store::new(&base).paths(false)?,
+            vec![path]
+        );
+
+        std::fs::remove_dir_all(&base)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn to_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let mut example: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+        example.request.method = http::Method::POST;
+        example.request.body = Some("q=1".into());
+        example.response.status = Some(http::StatusCode::CREATED);
+        example.tags = vec!["binary".to_string()];
+
+        let bytes = example.to_bytes()?;
+
+        assert!(bytes.len() < serde_json::to_vec(&example)?.len());
+        assert_eq!(Exchange::<serde_json::Value>::from_bytes(&bytes)?, example);
+
+        Ok(())
+    }
+
+    #[test]
+    fn into_json_value() -> Result<(), Box<dyn std::error::Error>> {
+        let example: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+        let expected = serde_json::to_value(&example)?;
+
+        assert_eq!(example.into_json_value()?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn content_range() {
+        let response = |value: &str| {
+            let mut headers = std::collections::HashMap::new();
+            headers.insert(
+                "Content-Range".into(),
+                crate::multi_value::MultiValue::new(value.to_string()),
+            );
+
+            Response::with_status(headers, (), http::StatusCode::PARTIAL_CONTENT)
+        };
+
+        assert_eq!(
+            response("bytes 0-1023/4096").content_range(),
+            Some(super::ContentRange {
+                start: 0,
+                end: 1023,
+                total: Some(4096)
+            })
+        );
+        assert_eq!(
+            response("bytes 1024-2047/*")
+                .content_range()
+                .map(|range| range.total),
+            Some(None)
+        );
+        assert_eq!(response("bytes */4096").content_range(), None);
+        assert!(response("bytes 0-1/2").is_partial());
+    }
 }
@@ -0,0 +1,109 @@
+/// Split a header value on commas that are not inside double quotes or angle brackets.
+pub fn split_list(value: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut in_brackets = false;
+    let mut escaped = false;
+
+    for (index, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if in_quotes {
+            match c {
+                '\\' => escaped = true,
+                '"' => in_quotes = false,
+                _ => {}
+            }
+        } else {
+            match c {
+                '"' => in_quotes = true,
+                '<' => in_brackets = true,
+                '>' => in_brackets = false,
+                ',' if !in_brackets => {
+                    parts.push(value[start..index].trim());
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    parts.push(value[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// Whether a media type (e.g. from a `Content-Type` header, possibly with parameters) is JSON.
+pub fn is_json_mime_type(mime_type: &str) -> bool {
+    let essence = mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    essence == "application/json" || essence == "text/json" || essence.ends_with("+json")
+}
+
+/// Parse `name=value` parameters separated by `;`, removing quotes from quoted values.
+///
+/// Names are lowercased.
+pub fn parse_params(value: &str) -> Vec<(String, String)> {
+    split_unquoted(value, ';')
+        .into_iter()
+        .filter_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            let name = name.trim();
+
+            (!name.is_empty()).then(|| (name.to_ascii_lowercase(), unquote(value.trim())))
+        })
+        .collect()
+}
+
+/// Remove surrounding double quotes (and backslash escapes) from a value, if it is quoted.
+pub fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .map_or_else(
+            || value.to_string(),
+            |inner| {
+                let mut result = String::with_capacity(inner.len());
+                let mut chars = inner.chars();
+
+                while let Some(c) = chars.next() {
+                    if c == '\\' {
+                        result.extend(chars.next());
+                    } else {
+                        result.push(c);
+                    }
+                }
+
+                result
+            },
+        )
+}
+
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (index, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && in_quotes {
+            escaped = true;
+        } else if c == '"' {
+            in_quotes = !in_quotes;
+        } else if c == separator && !in_quotes {
+            parts.push(value[start..index].trim());
+            start = index + c.len_utf8();
+        }
+    }
+
+    parts.push(value[start..].trim());
+    parts
+}
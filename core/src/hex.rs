@@ -0,0 +1,24 @@
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Encode bytes as lowercase hexadecimal.
+pub fn encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .flat_map(|byte| {
+            [
+                DIGITS[usize::from(byte >> 4)],
+                DIGITS[usize::from(byte & 0xf)],
+            ]
+        })
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn encode() {
+        assert_eq!(super::encode(&[]), "");
+        assert_eq!(super::encode(&[0x00, 0x0f, 0xa0, 0xff]), "000fa0ff");
+    }
+}
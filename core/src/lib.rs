@@ -2,7 +2,14 @@
 #![allow(clippy::missing_errors_doc)]
 #![forbid(unsafe_code)]
 pub mod archive;
+pub mod canonical;
 pub mod client;
+pub mod cookie;
+pub mod diff;
 pub mod exchange;
+mod header;
+mod hex;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod multi_value;
 pub mod request;
@@ -0,0 +1,119 @@
+//! Conversion of archived exchanges into [`wiremock`] mocks.
+
+use crate::exchange::{Error, Exchange};
+use http::header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, TRANSFER_ENCODING};
+use wiremock::matchers::{body_string, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A request matcher and response template derived from an archived exchange.
+///
+/// The matcher checks the method, path, query parameters, and body (if any), but not the request
+/// headers, which tend to vary between clients.
+#[derive(Clone, Debug)]
+pub struct MockSpec {
+    pub method: http::Method,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub response: ResponseTemplate,
+}
+
+impl MockSpec {
+    pub fn into_mock(self) -> Mock {
+        let mut builder = Mock::given(method(self.method.as_str())).and(path(self.path));
+
+        for (key, value) in self.query {
+            builder = builder.and(query_param(key, value));
+        }
+
+        if let Some(body) = self.body {
+            builder = builder.and(body_string(body));
+        }
+
+        builder.respond_with(self.response)
+    }
+
+    pub async fn mount(self, server: &MockServer) {
+        self.into_mock().mount(server).await;
+    }
+}
+
+impl<T: serde::ser::Serialize> Exchange<'_, T> {
+    /// Build a mock that responds to this exchange's request with its archived response.
+    ///
+    /// The raw response body is used if it was kept. Otherwise the data is serialized as JSON
+    /// (or used directly, if it serializes to a string). Headers describing the body's encoding on
+    /// the wire are dropped, as are headers that are not valid HTTP.
+    pub fn to_mock(&self) -> Result<MockSpec, Error> {
+        let (body, is_json) = match self.response.raw.as_deref() {
+            Some(raw) => (raw.as_bytes().to_vec(), false),
+            None => match serde_json::to_value(&self.response.data)? {
+                serde_json::Value::String(value) => (value.into_bytes(), false),
+                value => (serde_json::to_vec(&value)?, true),
+            },
+        };
+
+        let mut response = ResponseTemplate::new(
+            self.response
+                .status
+                .unwrap_or(http::StatusCode::OK)
+                .as_u16(),
+        );
+
+        for (name, values) in &self.response.headers {
+            let Ok(name) = http::HeaderName::try_from(name.as_ref()) else {
+                continue;
+            };
+
+            if [CONTENT_ENCODING, CONTENT_LENGTH, TRANSFER_ENCODING].contains(&name) {
+                continue;
+            }
+
+            for value in values {
+                if let Ok(value) = http::HeaderValue::try_from(value.as_ref()) {
+                    response = response.append_header(name.clone(), value);
+                }
+            }
+        }
+
+        let mime = if is_json && self.response.header(CONTENT_TYPE.as_str()).is_none() {
+            "application/json"
+        } else {
+            ""
+        };
+
+        Ok(MockSpec {
+            method: self.request.method.clone(),
+            path: self.request.url.path().to_string(),
+            query: self
+                .request
+                .url
+                .query_pairs()
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect(),
+            body: self.request.body.as_deref().map(str::to_string),
+            response: response.set_body_raw(body, mime),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exchange::Exchange;
+
+    const APPLE_ITUNES_01_EXAMPLE: &str = include_str!("../../examples/apple-itunes-01.json");
+
+    #[test]
+    fn to_mock() -> Result<(), Box<dyn std::error::Error>> {
+        let exchange: Exchange<'_, serde_json::Value> =
+            serde_json::from_str(APPLE_ITUNES_01_EXAMPLE)?;
+        let spec = exchange.to_mock()?;
+
+        assert_eq!(spec.method, http::Method::GET);
+        assert_eq!(spec.path, exchange.request.url.path());
+        assert_eq!(spec.query.len(), exchange.request.url.query_pairs().count());
+        assert_eq!(spec.body, None);
+
+        Ok(())
+    }
+}
@@ -25,6 +25,23 @@ impl<'a> MultiValue<'a> {
         }
     }
 
+    /// Create a value from a header value, replacing any invalid UTF-8 sequences.
+    #[must_use]
+    pub fn from_header_value_lossy(value: &http::HeaderValue) -> MultiValue<'static> {
+        MultiValue::new(String::from_utf8_lossy(value.as_bytes()).into_owned())
+    }
+
+    /// Collect values from an iterator, failing if it is empty.
+    pub fn try_from_iter<S: Into<Cow<'a, str>>, I: IntoIterator<Item = S>>(
+        values: I,
+    ) -> Result<Self, Error> {
+        let mut values = values.into_iter();
+        let mut result = Self::new(values.next().ok_or(Error::Empty)?);
+        result.extend(values);
+
+        Ok(result)
+    }
+
     pub fn push<S: Into<Cow<'a, str>>>(&mut self, value: S) {
         match &mut self.rest {
             None => {
@@ -36,6 +53,42 @@ impl<'a> MultiValue<'a> {
         }
     }
 
+    /// The number of values (always at least one).
+    #[must_use]
+    pub fn len(&self) -> usize {
+        1 + self.rest.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Always `false`, since there is always a first value.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The value at the given index, where index 0 is `first`.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Cow<'a, str>> {
+        if index == 0 {
+            Some(&self.first)
+        } else {
+            self.rest.as_ref().and_then(|rest| rest.get(index - 1))
+        }
+    }
+
+    /// All of the values, separated by `sep`.
+    #[must_use]
+    pub fn join(&self, sep: &str) -> String {
+        self.values().collect::<Vec<_>>().join(sep)
+    }
+
+    fn values(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.first.as_ref()).chain(self.rest.iter().flatten().map(AsRef::as_ref))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Cow<'a, str>> {
+        std::iter::once(&mut self.first).chain(self.rest.iter_mut().flatten())
+    }
+
     #[must_use]
     pub fn iter(&'a self) -> Iter<'a> {
         Iter {
@@ -45,6 +98,21 @@ impl<'a> MultiValue<'a> {
     }
 }
 
+/// Separates the values with `", "`, as in a combined header field (see RFC 9110, section 5.3).
+impl std::fmt::Display for MultiValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, value) in self.values().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+
+            f.write_str(value)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl<'a> AsRef<Cow<'a, str>> for MultiValue<'a> {
     fn as_ref(&self) -> &Cow<'a, str> {
         &self.first
@@ -55,15 +123,16 @@ impl<'a, S: Into<Cow<'a, str>>> TryFrom<Vec<S>> for MultiValue<'a> {
     type Error = Error;
 
     fn try_from(value: Vec<S>) -> Result<Self, Self::Error> {
-        let mut values = value.into_iter();
-
-        let first = values.next().ok_or(Error::Empty)?.into();
-        let rest: Vec<Cow<'a, str>> = values.map(Into::into).collect();
+        Self::try_from_iter(value)
+    }
+}
 
-        Ok(Self {
-            first,
-            rest: if rest.is_empty() { None } else { Some(rest) },
-        })
+/// Appends the values after the existing ones.
+impl<'a, S: Into<Cow<'a, str>>> Extend<S> for MultiValue<'a> {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, values: I) {
+        for value in values {
+            self.push(value);
+        }
     }
 }
 
@@ -177,6 +246,33 @@ impl serde::ser::Serialize for MultiValue<'_> {
     }
 }
 
+/// Serialize a value as an array, even if there is only one value.
+///
+/// This is intended for use with `#[serde(serialize_with = "...")]` for consumers that do not
+/// handle the singleton case. Deserialization accepts either representation.
+pub fn serialize_as_array<S: serde::ser::Serializer>(
+    value: &MultiValue<'_>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(value.iter())
+}
+
+/// Serialize a map of header values, representing every value as an array.
+pub fn serialize_map_as_arrays<K: serde::ser::Serialize, H, S: serde::ser::Serializer>(
+    values: &std::collections::HashMap<K, MultiValue<'_>, H>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    struct AsArray<'b, 'a>(&'b MultiValue<'a>);
+
+    impl serde::ser::Serialize for AsArray<'_, '_> {
+        fn serialize<S: serde::ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_as_array(self.0, serializer)
+        }
+    }
+
+    serializer.collect_map(values.iter().map(|(key, value)| (key, AsArray(value))))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::multi_value::MultiValue;
@@ -221,4 +317,139 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn len_and_get() -> Result<(), Box<dyn std::error::Error>> {
+        let singleton_example = MultiValue::new("test");
+        let multi_example: MultiValue<'_> = vec!["foo", "bar", "baz"].try_into()?;
+
+        assert_eq!(singleton_example.len(), 1);
+        assert_eq!(multi_example.len(), 3);
+        assert!(!multi_example.is_empty());
+        assert_eq!(multi_example.get(0).map(AsRef::as_ref), Some("foo"));
+        assert_eq!(multi_example.get(2).map(AsRef::as_ref), Some("baz"));
+        assert_eq!(multi_example.get(3), None);
+        assert_eq!(singleton_example.get(1), None);
+        Ok(())
+    }
+
+    #[derive(serde::Serialize)]
+    struct Headers<'a> {
+        #[serde(serialize_with = "super::serialize_map_as_arrays")]
+        headers: std::collections::HashMap<&'static str, MultiValue<'a>>,
+    }
+
+    #[test]
+    fn serialize_map_as_arrays() -> Result<(), Box<dyn std::error::Error>> {
+        let headers = Headers {
+            headers: [
+                ("accept", MultiValue::new("*/*")),
+                ("vary", vec!["a", "b"].try_into()?),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&headers)?,
+            serde_json::json!({"headers": {"accept": ["*/*"], "vary": ["a", "b"]}})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn from_header_value_lossy() -> Result<(), Box<dyn std::error::Error>> {
+        let valid = http::HeaderValue::from_static("attachment");
+        let invalid = http::HeaderValue::from_bytes(b"caf\xe9")?;
+
+        assert_eq!(
+            MultiValue::from_header_value_lossy(&valid),
+            MultiValue::new("attachment")
+        );
+        assert_eq!(
+            MultiValue::from_header_value_lossy(&invalid),
+            MultiValue::new("caf\u{fffd}")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn try_from_iter_and_extend() -> Result<(), Box<dyn std::error::Error>> {
+        let mut values = MultiValue::try_from_iter(["a", "b"].map(str::to_uppercase))?;
+        values.extend(["c", "d"]);
+        values.extend(std::iter::empty::<&str>());
+
+        assert_eq!(values.iter().collect::<Vec<_>>(), vec!["A", "B", "c", "d"]);
+        assert!(matches!(
+            MultiValue::try_from_iter(std::iter::empty::<&str>()),
+            Err(super::Error::Empty)
+        ));
+
+        let mut singleton = MultiValue::new("a");
+        singleton.extend(Vec::<String>::new());
+
+        assert_eq!(singleton, MultiValue::new("a"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bounded_static() -> Result<(), Box<dyn std::error::Error>> {
+        use bounded_static::{IntoBoundedStatic, ToBoundedStatic};
+
+        let source = vec!["foo".to_string(), "bar".to_string()];
+        let borrowed: MultiValue<'_> = source
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .try_into()?;
+
+        let cloned: MultiValue<'static> = borrowed.to_static();
+        let moved: MultiValue<'static> = borrowed.into_static();
+        drop(source);
+
+        assert_eq!(cloned, moved);
+        assert_eq!(moved.iter().collect::<Vec<_>>(), vec!["foo", "bar"]);
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_as_array() -> Result<(), Box<dyn std::error::Error>> {
+        #[derive(serde::Serialize)]
+        struct ArrayTest<'a> {
+            #[serde(serialize_with = "super::serialize_as_array")]
+            header_values: MultiValue<'a>,
+        }
+
+        let singleton_example = ArrayTest {
+            header_values: MultiValue::new("test"),
+        };
+
+        let multi_example = ArrayTest {
+            header_values: vec!["foo", "bar"].try_into()?,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&singleton_example)?,
+            r#"{"header_values":["test"]}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&multi_example)?,
+            r#"{"header_values":["foo","bar"]}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn join_and_display() -> Result<(), Box<dyn std::error::Error>> {
+        let single = MultiValue::new("gzip");
+        let multi = MultiValue::try_from(vec!["a", "b", "c"])?;
+
+        assert_eq!(single.join(","), "gzip");
+        assert_eq!(single.to_string(), "gzip");
+        assert_eq!(multi.join(","), "a,b,c");
+        assert_eq!(multi.to_string(), "a, b, c");
+
+        Ok(())
+    }
 }
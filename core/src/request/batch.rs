@@ -0,0 +1,121 @@
+use super::Request;
+use bounded_static::ToBoundedStatic;
+use std::fmt::Write;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid URL for ID")]
+pub struct Error {
+    pub id: String,
+    #[source]
+    pub error: url::ParseError,
+}
+
+/// Build one request per ID by substituting each ID for `placeholder` in the template.
+///
+/// In the URL, IDs are percent-encoded. In the body, they are escaped according to the
+/// template's `Content-Type`: as JSON string contents for JSON bodies, form-encoded for
+/// `application/x-www-form-urlencoded` bodies, and inserted as-is otherwise.
+///
+/// Fails with the first ID that would make the URL invalid (e.g. for a placeholder in the host).
+pub fn from_ids<I: IntoIterator<Item = String>>(
+    template: &Request<'_>,
+    placeholder: &str,
+    ids: I,
+) -> Result<Vec<Request<'static>>, Error> {
+    let content_type = template
+        .header("content-type")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    ids.into_iter()
+        .map(|id| {
+            let mut request = template.to_static();
+
+            let encoded_id = percent_encode(&id);
+
+            // URL parsing may have percent-encoded the placeholder.
+            match template
+                .url
+                .as_str()
+                .replace(placeholder, &encoded_id)
+                .replace(&percent_encode(placeholder), &encoded_id)
+                .parse()
+            {
+                Ok(url) => request.url = url,
+                Err(error) => return Err(Error { id, error }),
+            }
+
+            if let Some(body) = template.body.as_deref() {
+                let encoded = if content_type.contains("json") {
+                    let quoted = serde_json::Value::from(id.as_str()).to_string();
+
+                    quoted[1..quoted.len() - 1].to_string()
+                } else if content_type.contains("x-www-form-urlencoded") {
+                    url::form_urlencoded::byte_serialize(id.as_bytes()).collect()
+                } else {
+                    id
+                };
+
+                request.body = Some(body.replace(placeholder, &encoded).into());
+            }
+
+            Ok(request)
+        })
+        .collect()
+}
+
+/// Percent-encode everything except unreserved characters (see RFC 3986, section 2.3).
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            // Writing to a `String` cannot fail.
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::request::Request;
+
+    #[test]
+    fn from_ids() -> Result<(), Box<dyn std::error::Error>> {
+        let template = Request::new(
+            "https://example.com/apps/{id}?ref={id}",
+            None,
+            Some(http::Method::POST),
+            Some([("Content-Type", "application/json")]),
+            Some(r#"{"id": "{id}"}"#),
+        )?;
+
+        let requests = super::from_ids(
+            &template,
+            "{id}",
+            ["com.example".to_string(), "a b/\"c\"".to_string()],
+        )?;
+
+        assert_eq!(
+            requests[0].url.as_str(),
+            "https://example.com/apps/com.example?ref=com.example"
+        );
+        assert_eq!(
+            requests[1].url.as_str(),
+            "https://example.com/apps/a%20b%2F%22c%22?ref=a%20b%2F%22c%22"
+        );
+        assert_eq!(requests[1].body.as_deref(), Some(r#"{"id": "a b/\"c\""}"#));
+
+        let template = Request::builder("https://{host}.example.com/").build()?;
+        let error = super::from_ids(&template, "{host}", ["api".to_string(), "a:b".to_string()])
+            .unwrap_err();
+
+        assert_eq!(error.id, "a:b");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use super::{HeaderError, Request};
+use chrono::{DateTime, Utc};
+use http::Method;
+use indexmap::IndexMap;
+use std::borrow::Cow;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    #[error("Invalid URL")]
+    Url(#[from] url::ParseError),
+    #[error("Invalid header")]
+    Header(#[from] HeaderError),
+}
+
+/// A builder for [`Request`], created with [`Request::builder`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[must_use]
+pub struct RequestBuilder<'a> {
+    url: String,
+    timestamp: Option<DateTime<Utc>>,
+    method: Method,
+    headers: IndexMap<Cow<'a, str>, Cow<'a, str>>,
+    body: Option<Cow<'a, str>>,
+    proxy: Option<String>,
+}
+
+impl<'a> RequestBuilder<'a> {
+    pub(super) fn new<U: AsRef<str>>(url: U) -> Self {
+        Self {
+            url: url.as_ref().to_string(),
+            timestamp: None,
+            method: Method::GET,
+            headers: IndexMap::new(),
+            body: None,
+            proxy: None,
+        }
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Set the timestamp (the current time is used by default).
+    pub const fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Add a header, replacing any existing value with the same name (compared
+    /// case-insensitively).
+    pub fn header<K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>>(
+        mut self,
+        name: K,
+        value: V,
+    ) -> Self {
+        let name = name.into();
+
+        self.headers
+            .retain(|existing, _| !existing.eq_ignore_ascii_case(&name));
+        self.headers.insert(name, value.into());
+        self
+    }
+
+    pub fn headers<K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>, I: IntoIterator<Item = (K, V)>>(
+        mut self,
+        headers: I,
+    ) -> Self {
+        self.headers.extend(
+            headers
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into())),
+        );
+        self
+    }
+
+    pub fn body<B: Into<Cow<'a, str>>>(mut self, body: B) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Set an `application/x-www-form-urlencoded` body from the given pairs.
+    ///
+    /// This also sets the `Content-Type` header, and changes the method to `POST` if it is `GET`.
+    /// The pairs can be read back with [`Request::form_pairs`].
+    pub fn form<K: AsRef<str>, V: AsRef<str>, I: IntoIterator<Item = (K, V)>>(
+        mut self,
+        pairs: I,
+    ) -> Self {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(pairs)
+            .finish();
+
+        if self.method == Method::GET {
+            self.method = Method::POST;
+        }
+
+        self.headers
+            .retain(|name, _| !name.eq_ignore_ascii_case("content-type"));
+        self.headers.insert(
+            "Content-Type".into(),
+            "application/x-www-form-urlencoded".into(),
+        );
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Set the proxy label (see [`Request::via_proxy`]).
+    pub fn proxy<S: Into<String>>(mut self, label: S) -> Self {
+        self.proxy = Some(label.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Request<'a>, url::ParseError> {
+        Ok(Request {
+            url: self.url.parse()?,
+            timestamp: self.timestamp.unwrap_or_else(Utc::now),
+            method: self.method,
+            headers: self.headers,
+            body: self.body,
+            proxy: self.proxy,
+        })
+    }
+
+    /// Build the request, also checking that the header names and values are valid.
+    ///
+    /// Invalid headers are otherwise only detected when the request is sent.
+    pub fn build_checked(self) -> Result<Request<'a>, BuildError> {
+        let request = self.build()?;
+        request.validate_headers()?;
+
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildError;
+    use crate::request::{HeaderError, Request};
+
+    #[test]
+    fn build() -> Result<(), Box<dyn std::error::Error>> {
+        let timestamp = "2025-01-01T00:00:00Z".parse()?;
+        let request = Request::builder("https://example.com/api")
+            .method(http::Method::POST)
+            .timestamp(timestamp)
+            .header("Content-Type", "application/json")
+            .headers([("Accept", "application/json")])
+            .body(r#"{"a":1}"#)
+            .build()?;
+
+        let expected = Request::new(
+            "https://example.com/api",
+            Some(timestamp),
+            Some(http::Method::POST),
+            Some([
+                ("Content-Type", "application/json"),
+                ("Accept", "application/json"),
+            ]),
+            Some(r#"{"a":1}"#),
+        )?;
+
+        assert_eq!(request, expected);
+        assert!(Request::builder("not a url").build().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn build_checked() -> Result<(), Box<dyn std::error::Error>> {
+        let builder = Request::builder("https://example.com/")
+            .timestamp("2025-01-01T00:00:00Z".parse()?)
+            .header("Accept", "*/*");
+
+        assert_eq!(builder.clone().build_checked()?, builder.build()?);
+
+        assert!(matches!(
+            Request::builder("https://example.com/")
+                .header("X-Token", "abc\n")
+                .build_checked(),
+            Err(BuildError::Header(HeaderError::Value(_)))
+        ));
+        assert!(matches!(
+            Request::builder("https://example.com/")
+                .header("X Token", "abc")
+                .build_checked(),
+            Err(BuildError::Header(HeaderError::Name(_)))
+        ));
+        assert!(matches!(
+            Request::builder("not a url").build_checked(),
+            Err(BuildError::Url(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn header_replaces_case_insensitively() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::builder("https://example.com/")
+            .header("Accept", "text/html")
+            .header("accept", "application/json")
+            .build()?;
+
+        assert_eq!(request.headers.len(), 1);
+        assert_eq!(request.header("Accept"), Some("application/json"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn form() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::builder("https://example.com/login")
+            .header("content-type", "text/plain")
+            .form([("user", "jane doe"), ("next", "/a?b=c&d")])
+            .build()?;
+
+        assert_eq!(request.method, http::Method::POST);
+        assert_eq!(
+            request.header("Content-Type"),
+            Some("application/x-www-form-urlencoded")
+        );
+        assert_eq!(request.headers.len(), 1);
+        assert_eq!(
+            request.body.as_deref(),
+            Some("user=jane+doe&next=%2Fa%3Fb%3Dc%26d")
+        );
+        assert_eq!(
+            request.form_pairs().collect::<Vec<_>>(),
+            vec![
+                ("user".into(), "jane doe".into()),
+                ("next".into(), "/a?b=c&d".into())
+            ]
+        );
+
+        let request = Request::builder("https://example.com/items")
+            .method(http::Method::PUT)
+            .form([("id", "1")])
+            .build()?;
+
+        assert_eq!(request.method, http::Method::PUT);
+
+        Ok(())
+    }
+}
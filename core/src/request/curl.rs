@@ -0,0 +1,355 @@
+use super::Request;
+use http::Method;
+
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum CurlParseError {
+    #[error("Unterminated quote")]
+    UnterminatedQuote,
+    #[error("Missing value for option")]
+    MissingValue(String),
+    #[error("Invalid header")]
+    InvalidHeader(String),
+    #[error("Invalid method")]
+    InvalidMethod(String),
+    #[error("Missing URL")]
+    MissingUrl,
+    #[error("Invalid URL")]
+    InvalidUrl(#[from] url::ParseError),
+    /// An option that is not supported, which was skipped.
+    #[error("Unknown option")]
+    UnknownFlag(String),
+    /// An argument that is not an option or a URL, which was skipped.
+    #[error("Unexpected argument")]
+    UnexpectedArgument(String),
+}
+
+impl CurlParseError {
+    /// Whether parsing can continue after this error (it only indicates skipped input).
+    #[must_use]
+    pub const fn is_recoverable(&self) -> bool {
+        matches!(self, Self::UnknownFlag(_) | Self::UnexpectedArgument(_))
+    }
+}
+
+/// Options that are accepted but do not affect the request.
+const IGNORED_FLAGS: &[&str] = &[
+    "--compressed",
+    "-s",
+    "--silent",
+    "-S",
+    "--show-error",
+    "-L",
+    "--location",
+    "-k",
+    "--insecure",
+    "-i",
+    "--include",
+    "-v",
+    "--verbose",
+    "-g",
+    "--globoff",
+];
+
+const DATA_FLAGS: &[&str] = &[
+    "-d",
+    "--data",
+    "--data-raw",
+    "--data-binary",
+    "--data-ascii",
+];
+
+impl Request<'static> {
+    /// Parse a `curl` command (e.g. from a browser's "Copy as cURL"), skipping unknown options.
+    ///
+    /// See [`Self::from_curl_with_warnings`] for a list of the skipped input.
+    pub fn from_curl(input: &str) -> Result<Self, CurlParseError> {
+        Self::from_curl_with_warnings(input).map(|(request, _)| request)
+    }
+
+    /// Parse a `curl` command, also returning the recoverable errors for any skipped input.
+    ///
+    /// Supports `-X`/`--request`, `-H`/`--header`, the `-d`/`--data` family (multiple values are
+    /// joined with `&`, and the method defaults to `POST`), `-A`, `-b`, `-e`, `--url`, and a bare
+    /// URL argument. Single, double, and ANSI-C (`$'...'`) quoting and line continuations are
+    /// supported.
+    pub fn from_curl_with_warnings(
+        input: &str,
+    ) -> Result<(Self, Vec<CurlParseError>), CurlParseError> {
+        let mut args = split_args(input)?.into_iter().peekable();
+
+        if args.peek().is_some_and(|arg| arg == "curl") {
+            args.next();
+        }
+
+        let mut url = None;
+        let mut method = None;
+        let mut headers: Vec<(String, String)> = vec![];
+        let mut data: Vec<String> = vec![];
+        let mut warnings = vec![];
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') || arg == "-" {
+                if url.is_none() && arg.contains("://") {
+                    url = Some(arg);
+                } else {
+                    warnings.push(CurlParseError::UnexpectedArgument(arg));
+                }
+
+                continue;
+            }
+
+            if IGNORED_FLAGS.contains(&arg.as_str()) {
+                continue;
+            }
+
+            let (flag, inline_value) = split_flag(&arg);
+            let known = matches!(
+                flag,
+                "-X" | "--request"
+                    | "-H"
+                    | "--header"
+                    | "-A"
+                    | "--user-agent"
+                    | "-b"
+                    | "--cookie"
+                    | "-e"
+                    | "--referer"
+                    | "--url"
+            ) || DATA_FLAGS.contains(&flag);
+
+            if !known {
+                warnings.push(CurlParseError::UnknownFlag(arg));
+                continue;
+            }
+
+            let value = match inline_value {
+                Some(value) => value.to_string(),
+                None => args
+                    .next()
+                    .ok_or_else(|| CurlParseError::MissingValue(flag.to_string()))?,
+            };
+
+            match flag {
+                "-X" | "--request" => {
+                    method = Some(
+                        Method::from_bytes(value.as_bytes())
+                            .map_err(|_| CurlParseError::InvalidMethod(value))?,
+                    );
+                }
+                "-H" | "--header" => {
+                    let (name, value) = value
+                        .split_once(':')
+                        .ok_or_else(|| CurlParseError::InvalidHeader(value.clone()))?;
+
+                    headers.push((name.trim().to_string(), value.trim().to_string()));
+                }
+                "-A" | "--user-agent" => headers.push(("User-Agent".to_string(), value)),
+                "-b" | "--cookie" => headers.push(("Cookie".to_string(), value)),
+                "-e" | "--referer" => headers.push(("Referer".to_string(), value)),
+                "--url" => url = Some(value),
+                _ => data.push(value),
+            }
+        }
+
+        let url = url.ok_or(CurlParseError::MissingUrl)?;
+        let body = (!data.is_empty()).then(|| data.join("&"));
+
+        if body.is_some()
+            && !headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        {
+            // This is the content type `curl` sends for `--data`.
+            headers.push((
+                "Content-Type".to_string(),
+                "application/x-www-form-urlencoded".to_string(),
+            ));
+        }
+
+        let method = method.unwrap_or_else(|| {
+            if body.is_some() {
+                Method::POST
+            } else {
+                Method::GET
+            }
+        });
+
+        let request = Self::new(url, None, Some(method), Some(headers), body)?;
+
+        Ok((request, warnings))
+    }
+}
+
+/// Split `--name=value` and `-Xvalue` forms into the option and its value.
+fn split_flag(arg: &str) -> (&str, Option<&str>) {
+    if arg.starts_with("--") {
+        arg.split_once('=')
+            .map_or((arg, None), |(flag, value)| (flag, Some(value)))
+    } else if arg.len() > 2 && arg.is_char_boundary(2) {
+        (&arg[..2], Some(&arg[2..]))
+    } else {
+        (arg, None)
+    }
+}
+
+/// Split a command line into arguments, following POSIX shell quoting rules.
+fn split_args(input: &str) -> Result<Vec<String>, CurlParseError> {
+    let mut args = vec![];
+    let mut current: Option<String> = None;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                // Line continuations (and a trailing backslash) are removed.
+                Some('\n') | None => {}
+                Some(c) => current.get_or_insert_default().push(c),
+            },
+            '\'' => {
+                let arg = current.get_or_insert_default();
+
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err(CurlParseError::UnterminatedQuote),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_default();
+
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => arg.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                            None => return Err(CurlParseError::UnterminatedQuote),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(CurlParseError::UnterminatedQuote),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                let arg = current.get_or_insert_default();
+
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => arg.push('\n'),
+                            Some('r') => arg.push('\r'),
+                            Some('t') => arg.push('\t'),
+                            Some('x') => {
+                                let digits = chars
+                                    .clone()
+                                    .take(2)
+                                    .take_while(char::is_ascii_hexdigit)
+                                    .collect::<String>();
+
+                                for _ in 0..digits.len() {
+                                    chars.next();
+                                }
+
+                                arg.extend(
+                                    u32::from_str_radix(&digits, 16)
+                                        .ok()
+                                        .and_then(char::from_u32),
+                                );
+                            }
+                            Some(c) => arg.push(c),
+                            None => return Err(CurlParseError::UnterminatedQuote),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err(CurlParseError::UnterminatedQuote),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                args.extend(current.take());
+            }
+            c => current.get_or_insert_default().push(c),
+        }
+    }
+
+    args.extend(current);
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CurlParseError;
+    use crate::request::Request;
+
+    #[test]
+    fn from_curl() -> Result<(), Box<dyn std::error::Error>> {
+        let input = r#"curl 'https://example.com/api?q=1' \
+  -H 'accept: application/json' \
+  -H "x-quote: \"quoted\"" \
+  --data-raw '{"name":"O'\''Brien"}' \
+  --compressed --max-time 10"#;
+
+        let (request, warnings) = Request::from_curl_with_warnings(input)?;
+
+        assert_eq!(request.method, http::Method::POST);
+        assert_eq!(request.url.as_str(), "https://example.com/api?q=1");
+        assert_eq!(request.header("accept"), Some("application/json"));
+        assert_eq!(request.header("x-quote"), Some(r#""quoted""#));
+        assert_eq!(request.body.as_deref(), Some(r#"{"name":"O'Brien"}"#));
+        assert_eq!(
+            warnings,
+            vec![
+                CurlParseError::UnknownFlag("--max-time".to_string()),
+                CurlParseError::UnexpectedArgument("10".to_string()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_curl_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::builder("https://example.com/search?q=it's")
+            .method(http::Method::PUT)
+            .header("Content-Type", "text/plain")
+            .header("X-Note", "first\nsecond")
+            .body("a 'quoted' body")
+            .build()?;
+
+        let parsed = Request::from_curl(&request.to_curl_unredacted())?;
+
+        assert_eq!(parsed.method, request.method);
+        assert_eq!(parsed.url, request.url);
+        assert_eq!(parsed.headers, request.headers);
+        assert_eq!(parsed.body, request.body);
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_curl_errors() {
+        assert_eq!(
+            Request::from_curl("curl -H 'accept: */*'"),
+            Err(CurlParseError::MissingUrl)
+        );
+        assert_eq!(
+            Request::from_curl("curl 'https://example.com"),
+            Err(CurlParseError::UnterminatedQuote)
+        );
+        assert_eq!(
+            Request::from_curl("curl https://example.com -X"),
+            Err(CurlParseError::MissingValue("-X".to_string()))
+        );
+    }
+}
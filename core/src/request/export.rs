@@ -0,0 +1,301 @@
+use super::{Request, TargetForm};
+use http::Method;
+use std::fmt::Write;
+
+impl Request<'_> {
+    /// The request target to use in the request line, in the given form.
+    #[must_use]
+    pub fn request_target(&self, form: TargetForm) -> String {
+        request_target(&self.url, form)
+    }
+
+    /// A `curl` command that reproduces the request, with sensitive values redacted.
+    ///
+    /// See [`super::redact::SENSITIVE_NAMES`] for the names that are redacted, and
+    /// [`Self::to_curl_unredacted`] for the full command.
+    #[must_use]
+    pub fn to_curl(&self) -> String {
+        let url = self.redacted_url();
+
+        curl_command(
+            &self.method,
+            &url,
+            self.redacted_headers(),
+            self.body.as_deref(),
+        )
+    }
+
+    /// A `curl` command that reproduces the request exactly, including any credentials.
+    #[must_use]
+    pub fn to_curl_unredacted(&self) -> String {
+        curl_command(
+            &self.method,
+            &self.url,
+            self.headers
+                .iter()
+                .map(|(name, value)| (name.as_ref(), value.as_ref())),
+            self.body.as_deref(),
+        )
+    }
+
+    /// A JavaScript `fetch` call that reproduces the request, with sensitive values redacted.
+    #[must_use]
+    pub fn to_fetch_js(&self) -> String {
+        let mut options = serde_json::Map::new();
+
+        if self.method != Method::GET {
+            options.insert("method".to_string(), self.method.as_str().into());
+        }
+
+        if !self.headers.is_empty() {
+            options.insert(
+                "headers".to_string(),
+                self.redacted_headers()
+                    .map(|(name, value)| (name.to_string(), value.into()))
+                    .collect::<serde_json::Map<_, _>>()
+                    .into(),
+            );
+        }
+
+        if let Some(body) = &self.body {
+            options.insert("body".to_string(), body.as_ref().into());
+        }
+
+        let url = serde_json::Value::from(self.redacted_url().as_str());
+
+        if options.is_empty() {
+            format!("fetch({url});")
+        } else {
+            // Serializing a map of strings cannot fail.
+            let options = serde_json::to_string_pretty(&options).unwrap_or_default();
+
+            format!("fetch({url}, {options});")
+        }
+    }
+}
+
+/// Displays the request line, headers, and body, with sensitive values redacted.
+impl std::fmt::Display for Request<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let form = match TargetForm::for_method(&self.method) {
+            TargetForm::Origin => TargetForm::Absolute,
+            form => form,
+        };
+
+        write!(
+            f,
+            "{} {}",
+            self.method,
+            request_target(&self.redacted_url(), form)
+        )?;
+
+        for (name, value) in self.redacted_headers() {
+            write!(f, "\n{name}: {value}")?;
+        }
+
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{body}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn request_target(url: &url::Url, form: TargetForm) -> String {
+    match form {
+        TargetForm::Origin => {
+            let mut target = url.path().to_string();
+
+            if target.is_empty() {
+                target.push('/');
+            }
+
+            if let Some(query) = url.query() {
+                target.push('?');
+                target.push_str(query);
+            }
+
+            target
+        }
+        TargetForm::Absolute => {
+            let mut url = url.clone();
+            url.set_fragment(None);
+            url.to_string()
+        }
+        TargetForm::Authority => {
+            let host = url.host_str().unwrap_or_default();
+
+            url.port_or_known_default()
+                .map_or_else(|| host.to_string(), |port| format!("{host}:{port}"))
+        }
+    }
+}
+
+fn curl_command<'h, I: Iterator<Item = (&'h str, &'h str)>>(
+    method: &Method,
+    url: &url::Url,
+    headers: I,
+    body: Option<&str>,
+) -> String {
+    let mut command = "curl".to_string();
+
+    // Writing to a `String` cannot fail. `curl` sends a `POST` by default if there is a body.
+    if method != Method::GET || body.is_some() {
+        let _ = write!(command, " \\\n  -X {}", shell_quote(method.as_str()));
+    }
+
+    // `curl` sends the origin form by default.
+    let form = TargetForm::for_method(method);
+
+    if form != TargetForm::Origin {
+        let _ = write!(
+            command,
+            " \\\n  --request-target {}",
+            shell_quote(&request_target(url, form))
+        );
+    }
+
+    for (name, value) in headers {
+        let _ = write!(
+            command,
+            " \\\n  -H {}",
+            shell_quote(&format!("{name}: {value}"))
+        );
+    }
+
+    if let Some(body) = body {
+        let _ = write!(command, " \\\n  --data-raw {}", shell_quote(body));
+    }
+
+    let _ = write!(command, " \\\n  {}", shell_quote(url.as_str()));
+
+    command
+}
+
+/// Quote a value for a POSIX shell.
+///
+/// Values containing control characters (such as newlines) use ANSI-C quoting (`$'...'`) so that
+/// the command stays on one logical line.
+fn shell_quote(value: &str) -> String {
+    if value.chars().any(char::is_control) {
+        let mut quoted = "$'".to_string();
+
+        for c in value.chars() {
+            match c {
+                '\\' => quoted.push_str("\\\\"),
+                '\'' => quoted.push_str("\\'"),
+                '\n' => quoted.push_str("\\n"),
+                '\r' => quoted.push_str("\\r"),
+                '\t' => quoted.push_str("\\t"),
+                c if c.is_control() => {
+                    let _ = write!(quoted, "\\x{:02x}", u32::from(c));
+                }
+                c => quoted.push(c),
+            }
+        }
+
+        quoted.push('\'');
+        quoted
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::request::{Request, TargetForm};
+
+    #[test]
+    fn request_target() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::new(
+            "https://example.com/search?q=1#results",
+            None,
+            Some(http::Method::CONNECT),
+            None::<[(&str, &str); 0]>,
+            None::<&str>,
+        )?;
+
+        assert_eq!(request.request_target(TargetForm::Origin), "/search?q=1");
+        assert_eq!(
+            request.request_target(TargetForm::Absolute),
+            "https://example.com/search?q=1"
+        );
+        assert_eq!(
+            request.request_target(TargetForm::Authority),
+            "example.com:443"
+        );
+        assert!(request.to_string().starts_with("CONNECT example.com:443"));
+        assert!(
+            request
+                .to_curl()
+                .contains("--request-target 'example.com:443'")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn to_curl() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::new(
+            "https://example.com/api?q=it's&api_key=abc123",
+            None,
+            Some(http::Method::POST),
+            Some([
+                ("Content-Type", "application/json"),
+                ("Authorization", "Bearer abc123"),
+            ]),
+            Some(r#"{"name": "O'Brien"}"#),
+        )?;
+
+        assert_eq!(
+            request.to_curl(),
+            "curl \\\n  -X 'POST' \\\n  -H 'Content-Type: application/json' \\\n  -H 'Authorization: <redacted>' \\\n  --data-raw '{\"name\": \"O'\\''Brien\"}' \\\n  'https://example.com/api?q=it%27s&api_key=%3Credacted%3E'"
+        );
+        assert!(request.to_curl_unredacted().contains("Bearer abc123"));
+        assert!(!request.to_string().contains("abc123"));
+        assert!(!request.to_fetch_js().contains("abc123"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn to_curl_get_with_body() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::builder("https://example.com/search")
+            .body("q=1")
+            .build()?;
+
+        assert!(request.to_curl().contains("-X 'GET'"));
+
+        let parsed = Request::from_curl(&request.to_curl_unredacted())?;
+
+        assert_eq!(parsed.method, http::Method::GET);
+        assert_eq!(parsed.body, request.body);
+        assert_eq!(parsed.url, request.url);
+
+        // Without a body the method is omitted.
+        let request = Request::builder("https://example.com/search").build()?;
+
+        assert!(!request.to_curl().contains("-X"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn to_curl_header_newline() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::builder("https://example.com/")
+            .header("X-Note", "first\nsecond")
+            .build()?;
+
+        assert_eq!(
+            request.to_curl(),
+            "curl \\\n  -H $'X-Note: first\\nsecond' \\\n  'https://example.com/'"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn shell_quote_control_characters() {
+        assert_eq!(super::shell_quote("a\nb'c"), r"$'a\nb\'c'");
+    }
+}
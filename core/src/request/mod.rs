@@ -9,7 +9,13 @@ use serde_field_attributes::{represented_as_str, timestamp_millis_str};
 use std::borrow::Cow;
 use url::Url;
 
+pub mod batch;
+pub mod builder;
+pub mod curl;
+mod export;
+pub mod multipart;
 pub mod params;
+pub mod redact;
 
 #[derive(Debug, thiserror::Error)]
 pub enum HeaderError {
@@ -19,6 +25,29 @@ pub enum HeaderError {
     Value(#[from] http::header::InvalidHeaderValue),
 }
 
+/// The form of the request target in an HTTP/1.1 request line (see RFC 9112, section 3.2).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TargetForm {
+    /// The path and query (e.g. `/search?q=1`).
+    Origin,
+    /// The full URL (e.g. `https://example.com/search?q=1`), as sent to proxies.
+    Absolute,
+    /// The host and port (e.g. `example.com:443`), as used by `CONNECT`.
+    Authority,
+}
+
+impl TargetForm {
+    /// The form a client would use when sending a request with this method directly to a server.
+    #[must_use]
+    pub fn for_method(method: &Method) -> Self {
+        if method == Method::CONNECT {
+            Self::Authority
+        } else {
+            Self::Origin
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Request<'a> {
     pub url: Url,
@@ -34,9 +63,17 @@ pub struct Request<'a> {
     pub headers: IndexMap<Cow<'a, str>, Cow<'a, str>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub body: Option<Cow<'a, str>>,
+    /// A label for the proxy (or other egress) the request was sent through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
 }
 
 impl<'a> Request<'a> {
+    /// Start building a request for the given URL.
+    pub fn builder<U: AsRef<str>>(url: U) -> builder::RequestBuilder<'a> {
+        builder::RequestBuilder::new(url)
+    }
+
     pub fn new<
         U: AsRef<str>,
         K: Into<Cow<'a, str>>,
@@ -63,9 +100,432 @@ impl<'a> Request<'a> {
                 })
                 .unwrap_or_default(),
             body: body.map(std::convert::Into::into),
+            proxy: None,
         })
     }
 
+    /// Record the proxy the request is sent through, which is kept in the archived exchange.
+    ///
+    /// The label is only metadata: the client's proxy configuration is not affected.
+    pub fn via_proxy(&mut self, label: &str) {
+        self.proxy = Some(label.to_string());
+    }
+
+    /// Replace the body with a `multipart/form-data` encoding of the given parts.
+    ///
+    /// This also sets the `Content-Type` header (including the generated boundary). Since request
+    /// bodies are stored as strings, byte parts must be valid UTF-8: binary data (such as images)
+    /// is rejected with an error naming the part, and the request is left unchanged.
+    pub fn multipart_body<I: IntoIterator<Item = (String, multipart::MultipartPart)>>(
+        &mut self,
+        parts: I,
+    ) -> Result<(), multipart::NonUtf8PartError> {
+        let parts = parts.into_iter().collect::<Vec<_>>();
+        let (boundary, body) = multipart::encode(&parts)?;
+
+        self.set_header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={boundary}"),
+        );
+        self.body = Some(body.into());
+
+        Ok(())
+    }
+
+    /// Copy the named query parameters from another request's URL into this request's URL.
+    ///
+    /// Existing values for each key are replaced in place (or appended if the key is not already
+    /// present). Keys that do not appear in `other`'s URL are left unchanged.
+    pub fn merge_query_from(&mut self, other: &Request<'_>, keys: &[&str]) {
+        // We work with the raw segments, so that parameters that are not merged are kept exactly.
+        let mut segments = self
+            .url
+            .query()
+            .map(|query| {
+                query
+                    .split('&')
+                    .filter(|segment| !segment.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        for key in keys {
+            let values = other
+                .url
+                .query_pairs()
+                .filter(|(other_key, _)| other_key == key)
+                .map(|(_, value)| value.into_owned())
+                .collect::<Vec<_>>();
+
+            if values.is_empty() {
+                continue;
+            }
+
+            let merged = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(values.iter().map(|value| (key, value)))
+                .finish();
+            let is_key = |segment: &str| {
+                url::form_urlencoded::parse(segment.as_bytes())
+                    .next()
+                    .is_some_and(|(segment_key, _)| segment_key == *key)
+            };
+
+            match segments.iter().position(|segment| is_key(segment)) {
+                Some(index) => {
+                    segments[index] = merged;
+                    let mut current = 0;
+
+                    segments.retain(|segment| {
+                        let keep = current == index || !is_key(segment);
+                        current += 1;
+                        keep
+                    });
+                }
+                None => segments.push(merged),
+            }
+        }
+
+        if !segments.is_empty() {
+            self.url.set_query(Some(&segments.join("&")));
+        }
+    }
+
+    /// Remove repeated query parameters where both the key and the value are duplicates.
+    ///
+    /// Only exact duplicates are removed, so genuinely repeated parameters such as
+    /// `id=1&id=2` are kept, while `a=1&a=1` becomes `a=1`. The first occurrence of each pair is
+    /// kept and the order is preserved. See [`Self::dedup_query_keys`] for removing all repeated
+    /// keys.
+    pub fn dedup_query_params(&mut self) {
+        self.dedup_query_segments(false);
+    }
+
+    /// Remove repeated query parameters by key, keeping only the first occurrence of each key.
+    pub fn dedup_query_keys(&mut self) {
+        self.dedup_query_segments(true);
+    }
+
+    // Filters the raw query segments (without re-encoding the kept ones), comparing decoded keys
+    // and values.
+    fn dedup_query_segments(&mut self, by_key: bool) {
+        if let Some(query) = self.url.query() {
+            let mut seen = std::collections::HashSet::new();
+            let query = query
+                .split('&')
+                .filter(|segment| {
+                    segment.is_empty() || {
+                        let (key, value) = url::form_urlencoded::parse(segment.as_bytes())
+                            .next()
+                            .unwrap_or_default();
+
+                        seen.insert((key, if by_key { Cow::Borrowed("") } else { value }))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("&");
+
+            self.url.set_query(Some(&query));
+        }
+    }
+
+    /// A normalized copy of the request's URL, for comparing requests.
+    ///
+    /// The following normalizations are applied:
+    ///
+    /// * the host is lowercased and default ports are removed (the `url` crate already does this
+    ///   when parsing URLs with special schemes such as `https`);
+    /// * a trailing slash is removed from non-root paths;
+    /// * query parameters with keys in `strip_params` (e.g. tracking parameters like
+    ///   `utm_source`) are removed;
+    /// * the remaining query parameters are sorted by key and then value, and re-encoded, and an
+    ///   empty query is removed;
+    /// * the fragment is removed.
+    ///
+    /// The `url` field itself is not changed.
+    #[must_use]
+    pub fn normalized_url(&self, strip_params: &[&str]) -> Url {
+        let mut url = self.url.clone();
+
+        if let Some(url::Host::Domain(domain)) = url.host() {
+            let domain = domain.to_ascii_lowercase();
+            // This can only fail for URLs that cannot have a host, which this one does.
+            let _ = url.set_host(Some(&domain));
+        }
+
+        if url.port().is_some() && url.port() == default_port(url.scheme()) {
+            let _ = url.set_port(None);
+        }
+
+        if url.path().len() > 1 && url.path().ends_with('/') {
+            let path = url.path().trim_end_matches('/').to_string();
+            url.set_path(if path.is_empty() { "/" } else { &path });
+        }
+
+        let mut pairs = url
+            .query_pairs()
+            .filter(|(key, _)| !strip_params.contains(&key.as_ref()))
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect::<Vec<_>>();
+        pairs.sort();
+
+        if pairs.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(pairs);
+        }
+
+        url.set_fragment(None);
+
+        url
+    }
+
+    /// A canonical string representation of the request, for use as a cache key.
+    ///
+    /// The key includes the method, URL, headers (with lowercased names, sorted), and body, but
+    /// not the timestamp. JSON bodies are canonicalized (see [`Self::canonicalize_body`]).
+    #[must_use]
+    pub fn cache_key(&self) -> String {
+        let mut key = format!("{} {}", self.method, self.url);
+
+        for (name, value) in self.canonical_headers() {
+            key.push('\n');
+            key.push_str(&name);
+            key.push_str(": ");
+            key.push_str(value);
+        }
+
+        if let Some(body) = &self.body {
+            key.push_str("\n\n");
+            key.push_str(&canonical_json_body(body).unwrap_or_else(|| body.to_string()));
+        }
+
+        key
+    }
+
+    /// Compare the method, URL, headers, and body, ignoring header order and header name case.
+    ///
+    /// Unlike [`Self::cache_key`], bodies are compared exactly. The timestamp and proxy are not
+    /// compared.
+    #[must_use]
+    pub fn canonical_eq(&self, other: &Request<'_>) -> bool {
+        self.method == other.method
+            && self.url == other.url
+            && self.body == other.body
+            && self.canonical_headers() == other.canonical_headers()
+    }
+
+    /// Hash the request consistently with [`Self::canonical_eq`].
+    pub fn canonical_hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        self.method.hash(state);
+        self.url.hash(state);
+        self.body.hash(state);
+        self.canonical_headers().hash(state);
+    }
+
+    /// The headers with lowercased names, sorted.
+    fn canonical_headers(&self) -> Vec<(String, &str)> {
+        let mut headers = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.as_ref()))
+            .collect::<Vec<_>>();
+        headers.sort_unstable();
+        headers
+    }
+
+    /// The [`Self::cache_key`] as a cheaply cloneable shared string, suitable for in-process
+    /// caches.
+    #[must_use]
+    pub fn cache_entry_key(&self) -> std::sync::Arc<str> {
+        self.cache_key().into()
+    }
+
+    /// A deterministic name-based (version 5) UUID for the request, derived from
+    /// [`Self::cache_key`] in the given namespace.
+    #[must_use]
+    pub fn derive_uuid(&self, namespace: uuid::Uuid) -> uuid::Uuid {
+        uuid::Uuid::new_v5(&namespace, self.cache_key().as_bytes())
+    }
+
+    /// Replace a JSON body with its canonical form (compact, with sorted keys).
+    ///
+    /// Bodies that are not valid JSON are left unchanged.
+    pub fn canonicalize_body(&mut self) {
+        if let Some(body) = self.body.as_deref().and_then(canonical_json_body) {
+            self.body = Some(body.into());
+        }
+    }
+
+    /// Remove parts of the request that the client would not actually send.
+    ///
+    /// Currently this drops the body for `GET` and `HEAD` requests (where bodies are
+    /// non-standard), unless `retain_body` is set.
+    pub fn clean(&mut self, retain_body: bool) {
+        if !retain_body && (self.method == Method::GET || self.method == Method::HEAD) {
+            self.body = None;
+        }
+    }
+
+    /// The request's host in ASCII form, with international domain names in punycode.
+    ///
+    /// URLs with special schemes (e.g. `https`) are already normalized when parsed, so Unicode and
+    /// punycode versions of the same host compare equal here.
+    #[must_use]
+    pub fn ascii_host(&self) -> Option<String> {
+        match self.url.host()? {
+            url::Host::Domain(domain) => Some(
+                url::Host::parse(domain)
+                    .map_or_else(|_| domain.to_ascii_lowercase(), |host| host.to_string()),
+            ),
+            host => Some(host.to_string()),
+        }
+    }
+
+    /// The decoded query parameters, in order.
+    pub fn query_pairs(&self) -> impl Iterator<Item = (Cow<'_, str>, Cow<'_, str>)> {
+        self.url.query_pairs()
+    }
+
+    /// The decoded pairs from an `application/x-www-form-urlencoded` body, in order.
+    ///
+    /// The content type is not checked, and there are no pairs if there is no body.
+    pub fn form_pairs(&self) -> impl Iterator<Item = (Cow<'_, str>, Cow<'_, str>)> {
+        url::form_urlencoded::parse(self.body.as_deref().unwrap_or_default().as_bytes())
+    }
+
+    /// The decoded value of the first query parameter with the given key.
+    #[must_use]
+    pub fn query_param(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Look up a header value by name (compared case-insensitively).
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_ref())
+    }
+
+    /// The value of the `Content-Type` header, if there is one.
+    #[must_use]
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("content-type")
+    }
+
+    /// Parse the body as JSON, if there is one and the content type is JSON.
+    ///
+    /// Returns `None` for other content types and for bodies that are not valid JSON.
+    #[must_use]
+    pub fn body_json(&self) -> Option<serde_json::Value> {
+        if self
+            .content_type()
+            .is_some_and(crate::header::is_json_mime_type)
+        {
+            serde_json::from_str(self.body.as_deref()?).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Parse the name-value pairs from the `Cookie` header (see RFC 6265, section 5.4).
+    ///
+    /// Pairs without a `=` are skipped, and surrounding double quotes are not removed from values.
+    #[must_use]
+    pub fn cookies(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("cookie"))
+            .flat_map(|(_, value)| value.split(';'))
+            .filter_map(|pair| {
+                pair.split_once('=').and_then(|(name, value)| {
+                    let name = name.trim();
+
+                    (!name.is_empty()).then(|| (name.to_string(), value.trim().to_string()))
+                })
+            })
+            .collect()
+    }
+
+    /// Set a header value, replacing any existing values for the name (compared
+    /// case-insensitively).
+    pub(crate) fn set_header<K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>>(
+        &mut self,
+        name: K,
+        value: V,
+    ) {
+        let name = name.into();
+
+        self.headers
+            .retain(|existing, _| !existing.eq_ignore_ascii_case(&name));
+        self.headers.insert(name, value.into());
+    }
+
+    /// Build a complete `reqwest` request (rather than a builder), for use with middleware that
+    /// needs the realized request.
+    pub fn to_reqwest(
+        &self,
+        client: &reqwest::Client,
+    ) -> Result<reqwest::Request, crate::client::Error> {
+        Ok(crate::client::build_request(client, self)?.build()?)
+    }
+
+    /// Build a `hyper` request with the body as a `String` (empty when there is no body).
+    #[cfg(feature = "hyper")]
+    pub fn to_hyper(&self) -> Result<hyper::Request<String>, crate::client::Error> {
+        let mut builder = hyper::Request::builder()
+            .method(self.method.clone())
+            .uri(self.url.as_str());
+
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(self.header_map()?);
+        }
+
+        Ok(builder.body(self.body.as_deref().unwrap_or_default().to_string())?)
+    }
+
+    /// The headers with names cased for the given HTTP version.
+    ///
+    /// HTTP/2 and later require lowercase names, while HTTP/1.x names keep their original casing.
+    #[must_use]
+    pub fn headers_for_version(&self, version: http::Version) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        let lowercase = !matches!(
+            version,
+            http::Version::HTTP_09 | http::Version::HTTP_10 | http::Version::HTTP_11
+        );
+
+        self.headers
+            .iter()
+            .map(|(name, value)| {
+                let name = if lowercase && name.bytes().any(|byte| byte.is_ascii_uppercase()) {
+                    Cow::Owned(name.to_ascii_lowercase())
+                } else {
+                    Cow::Borrowed(name.as_ref())
+                };
+
+                (name, Cow::Borrowed(value.as_ref()))
+            })
+            .collect()
+    }
+
+    /// Check that every header name and value is valid, without building a header map.
+    ///
+    /// See [`builder::RequestBuilder::build_checked`] for validating at construction.
+    pub fn validate_headers(&self) -> Result<(), HeaderError> {
+        for (name, value) in &self.headers {
+            HeaderName::try_from(name.as_ref())?;
+            HeaderValue::try_from(value.as_ref())?;
+        }
+
+        Ok(())
+    }
+
     pub fn header_map(&self) -> Result<HeaderMap, HeaderError> {
         self.headers
             .iter()
@@ -95,6 +555,7 @@ impl IntoBoundedStatic for Request<'_> {
             body: self
                 .body
                 .map(bounded_static::IntoBoundedStatic::into_static),
+            proxy: self.proxy,
         }
     }
 }
@@ -116,10 +577,392 @@ impl ToBoundedStatic for Request<'_> {
                 .body
                 .as_ref()
                 .map(bounded_static::ToBoundedStatic::to_static),
+            proxy: self.proxy.clone(),
         }
     }
 }
 
+fn canonical_json_body(body: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| crate::canonical::to_string(&value).ok())
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        "ftp" => Some(21),
+        _ => None,
+    }
+}
+
 fn is_method_get(method: &Method) -> bool {
     method == Method::GET
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Request;
+
+    fn get(url: &str) -> Result<Request<'static>, url::ParseError> {
+        Request::new(url, None, None, None::<[(&str, &str); 0]>, None::<&str>)
+    }
+
+    #[test]
+    fn dedup_query() -> Result<(), Box<dyn std::error::Error>> {
+        let mut request = get("https://example.com/?a=1&id=1&a=1&id=2&b=x%20y&b=x+y")?;
+        request.dedup_query_params();
+
+        assert_eq!(request.url.query(), Some("a=1&id=1&id=2&b=x%20y"));
+
+        request.dedup_query_keys();
+
+        assert_eq!(request.url.query(), Some("a=1&id=1&b=x%20y"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn cookies() -> Result<(), Box<dyn std::error::Error>> {
+        let mut request = get("https://example.com/")?;
+        request.set_header("COOKIE", "a=1; b = two ;empty=; invalid; c=x=y");
+
+        assert_eq!(
+            request.cookies(),
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "two".to_string()),
+                ("empty".to_string(), String::new()),
+                ("c".to_string(), "x=y".to_string()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_query_from() -> Result<(), Box<dyn std::error::Error>> {
+        let mut request = get("https://example.com/items?q=test&page=1&limit=10")?;
+        let other = get("https://example.com/items?q=other&page=2&cursor=abc")?;
+
+        request.merge_query_from(&other, &["page", "cursor", "missing"]);
+
+        assert_eq!(
+            request.url.query(),
+            Some("q=test&page=2&limit=10&cursor=abc")
+        );
+
+        let mut request = get("https://example.com/items?token=abc==&q=a%20b&page=1&page=3")?;
+        let other = get("https://example.com/items?page=2&page=a%20b")?;
+
+        request.merge_query_from(&other, &["page"]);
+
+        assert_eq!(
+            request.url.query(),
+            Some("token=abc==&q=a%20b&page=2&page=a+b")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonicalize_body() -> Result<(), Box<dyn std::error::Error>> {
+        let mut request = get("https://example.com/")?;
+        request.body = Some(r#"{ "b": [1, {"d": 2, "c": 3}], "a": null }"#.into());
+
+        let mut other = request.clone();
+        other.body = Some(r#"{"a":null,"b":[1,{"c":3,"d":2}]}"#.into());
+
+        assert_eq!(request.cache_key(), other.cache_key());
+
+        request.canonicalize_body();
+
+        assert_eq!(request.body, other.body);
+
+        request.body = Some("a=1&b=2".into());
+        request.canonicalize_body();
+
+        assert_eq!(request.body.as_deref(), Some("a=1&b=2"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn clean() -> Result<(), Box<dyn std::error::Error>> {
+        for method in [http::Method::GET, http::Method::HEAD] {
+            let mut request = get("https://example.com/")?;
+            request.method = method;
+            request.body = Some("a=1".into());
+
+            let mut retained = request.clone();
+            retained.clean(true);
+
+            assert_eq!(retained.body.as_deref(), Some("a=1"));
+
+            request.clean(false);
+
+            assert_eq!(request.body, None);
+        }
+
+        let mut request = get("https://example.com/")?;
+        request.method = http::Method::POST;
+        request.body = Some("a=1".into());
+        request.clean(false);
+
+        assert_eq!(request.body.as_deref(), Some("a=1"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn to_reqwest() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::new(
+            "https://example.com/search?q=1",
+            None,
+            Some(http::Method::POST),
+            Some([("content-type", "application/json"), ("X-Token", "abc")]),
+            Some(r#"{"a":1}"#),
+        )?;
+
+        let built = request.to_reqwest(&reqwest::Client::new())?;
+
+        assert_eq!(built.method(), http::Method::POST);
+        assert_eq!(built.url(), &request.url);
+        assert_eq!(
+            built
+                .headers()
+                .get("content-type")
+                .map(http::HeaderValue::as_bytes),
+            Some(b"application/json".as_slice())
+        );
+        assert_eq!(
+            built
+                .headers()
+                .get("x-token")
+                .map(http::HeaderValue::as_bytes),
+            Some(b"abc".as_slice())
+        );
+        assert_eq!(
+            built.body().and_then(reqwest::Body::as_bytes),
+            Some(br#"{"a":1}"#.as_slice())
+        );
+
+        let mut invalid = request;
+        invalid.headers.insert("X-Invalid".into(), "a\nb".into());
+
+        assert!(matches!(
+            invalid.to_reqwest(&reqwest::Client::new()),
+            Err(crate::client::Error::Header(_))
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "hyper")]
+    #[test]
+    fn to_hyper() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::new(
+            "https://example.com/search?q=1",
+            None,
+            Some(http::Method::POST),
+            Some([("content-type", "application/json")]),
+            Some(r#"{"a":1}"#),
+        )?;
+        let hyper_request = request.to_hyper()?;
+
+        assert_eq!(hyper_request.method(), http::Method::POST);
+        assert_eq!(hyper_request.uri(), "https://example.com/search?q=1");
+        assert_eq!(hyper_request.headers()["content-type"], "application/json");
+        assert_eq!(hyper_request.body(), r#"{"a":1}"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ascii_host() -> Result<(), Box<dyn std::error::Error>> {
+        let unicode = get("https://例え.JP/path")?;
+        let punycode = get("https://xn--r8jz45g.jp/path")?;
+
+        assert_eq!(unicode.ascii_host().as_deref(), Some("xn--r8jz45g.jp"));
+        assert_eq!(unicode.ascii_host(), punycode.ascii_host());
+        assert_eq!(
+            get("custom://例え.jp/")?.ascii_host().as_deref(),
+            Some("xn--r8jz45g.jp")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn headers_for_version() -> Result<(), Box<dyn std::error::Error>> {
+        let mut request = get("https://example.com/")?;
+        request.set_header("Accept-Language", "en-US");
+
+        assert_eq!(
+            request.headers_for_version(http::Version::HTTP_11),
+            vec![("Accept-Language".into(), "en-US".into())]
+        );
+        assert_eq!(
+            request.headers_for_version(http::Version::HTTP_2),
+            vec![("accept-language".into(), "en-US".into())]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn query_param() -> Result<(), Box<dyn std::error::Error>> {
+        let request = get("https://example.com/?hl=en&gl=us&q=a+b%26c&hl=fr")?;
+
+        assert_eq!(request.query_param("hl").as_deref(), Some("en"));
+        assert_eq!(request.query_param("q").as_deref(), Some("a b&c"));
+        assert_eq!(request.query_param("missing"), None);
+        assert_eq!(request.query_pairs().count(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn derive_uuid() -> Result<(), Box<dyn std::error::Error>> {
+        let mut request = get("https://example.com/?q=1")?;
+        let uuid = request.derive_uuid(uuid::Uuid::NAMESPACE_URL);
+
+        assert_eq!(uuid.get_version_num(), 5);
+
+        request.timestamp += chrono::TimeDelta::seconds(1);
+
+        assert_eq!(request.derive_uuid(uuid::Uuid::NAMESPACE_URL), uuid);
+        assert_ne!(request.derive_uuid(uuid::Uuid::NAMESPACE_OID), uuid);
+
+        request.url.set_query(Some("q=2"));
+
+        assert_ne!(request.derive_uuid(uuid::Uuid::NAMESPACE_URL), uuid);
+
+        Ok(())
+    }
+
+    #[test]
+    fn body_json() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::builder("https://example.com/api")
+            .header("CONTENT-TYPE", "application/vnd.api+json; charset=utf-8")
+            .body(r#"{"a":[1,2]}"#)
+            .build()?;
+
+        assert_eq!(
+            request.content_type(),
+            Some("application/vnd.api+json; charset=utf-8")
+        );
+        assert_eq!(request.body_json(), Some(serde_json::json!({"a": [1, 2]})));
+
+        let form = Request::builder("https://example.com/api")
+            .form([("a", "1")])
+            .build()?;
+
+        assert_eq!(form.body_json(), None);
+
+        let invalid = Request::builder("https://example.com/api")
+            .header("Content-Type", "application/json")
+            .body("{")
+            .build()?;
+
+        assert_eq!(invalid.body_json(), None);
+        assert_eq!(get("https://example.com/")?.content_type(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn normalized_url() -> Result<(), Box<dyn std::error::Error>> {
+        let first = get("HTTPS://Example.COM:443/search/?q=a+b&utm_source=x&hl=en#results")?;
+        let second = get("https://example.com/search?hl=en&q=a%20b")?;
+
+        assert_eq!(
+            first.normalized_url(&["utm_source"]).as_str(),
+            "https://example.com/search?hl=en&q=a+b"
+        );
+        assert_eq!(
+            first.normalized_url(&["utm_source"]),
+            second.normalized_url(&[])
+        );
+        assert_eq!(
+            first.url.as_str(),
+            "https://example.com/search/?q=a+b&utm_source=x&hl=en#results"
+        );
+        assert_eq!(
+            get("https://example.com/?utm_source=x")?
+                .normalized_url(&["utm_source"])
+                .as_str(),
+            "https://example.com/"
+        );
+        assert_eq!(
+            get("custom://Example.com:443/a//")?
+                .normalized_url(&[])
+                .as_str(),
+            "custom://example.com:443/a"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_eq() -> Result<(), Box<dyn std::error::Error>> {
+        use std::hash::BuildHasher;
+
+        let first = Request::builder("https://example.com/api")
+            .header("Accept", "application/json")
+            .header("X-Token", "abc")
+            .build()?;
+        let mut second = Request::builder("https://example.com/api")
+            .header("x-token", "abc")
+            .header("accept", "application/json")
+            .build()?;
+        let hasher = std::hash::RandomState::new();
+
+        assert_ne!(first, second);
+        assert!(first.canonical_eq(&second));
+        assert_eq!(
+            hasher.hash_one(CanonicalHash(&first)),
+            hasher.hash_one(CanonicalHash(&second))
+        );
+
+        second.set_header("x-token", "def");
+
+        assert!(!first.canonical_eq(&second));
+        assert_ne!(
+            hasher.hash_one(CanonicalHash(&first)),
+            hasher.hash_one(CanonicalHash(&second))
+        );
+
+        Ok(())
+    }
+
+    struct CanonicalHash<'a>(&'a Request<'a>);
+
+    impl std::hash::Hash for CanonicalHash<'_> {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            self.0.canonical_hash(state);
+        }
+    }
+
+    #[test]
+    fn via_proxy() -> Result<(), Box<dyn std::error::Error>> {
+        let mut request = get("https://example.com/")?;
+
+        assert!(!serde_json::to_string(&request)?.contains("proxy"));
+
+        let key = request.cache_key();
+        request.via_proxy("residential-3");
+
+        let json = serde_json::to_string(&request)?;
+
+        assert!(json.contains(r#""proxy":"residential-3""#));
+        assert_eq!(
+            serde_json::from_str::<Request<'_>>(&json)?.proxy.as_deref(),
+            Some("residential-3")
+        );
+        assert_eq!(request.cache_key(), key);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt::Write;
+
+const BOUNDARY_PREFIX: &str = "scraper-trail-boundary";
+
+/// A byte part that cannot be stored in a request body, which must be a string.
+#[derive(Debug, thiserror::Error)]
+#[error("Multipart byte part is not valid UTF-8")]
+pub struct NonUtf8PartError {
+    pub name: String,
+    #[source]
+    pub error: std::str::Utf8Error,
+}
+
+/// A single part of a `multipart/form-data` request body.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MultipartPart {
+    Text(String),
+    Bytes {
+        data: Vec<u8>,
+        filename: Option<String>,
+        content_type: Option<String>,
+    },
+}
+
+impl MultipartPart {
+    fn contains(&self, boundary: &str) -> bool {
+        match self {
+            Self::Text(text) => text.contains(boundary),
+            Self::Bytes { data, .. } => data
+                .windows(boundary.len())
+                .any(|window| window == boundary.as_bytes()),
+        }
+    }
+}
+
+/// Encode the given parts, returning the boundary and the body.
+///
+/// The boundary is deterministic (the first candidate that does not appear in any part), so that
+/// the same parts always produce the same body.
+pub(super) fn encode(
+    parts: &[(String, MultipartPart)],
+) -> Result<(String, String), NonUtf8PartError> {
+    let mut counter = 0_usize;
+    let mut boundary = BOUNDARY_PREFIX.to_string();
+
+    while parts
+        .iter()
+        .any(|(name, part)| name.contains(&boundary) || part.contains(&boundary))
+    {
+        counter += 1;
+        boundary = format!("{BOUNDARY_PREFIX}-{counter}");
+    }
+
+    let mut body = String::new();
+
+    for (name, part) in parts {
+        // Writing to a `String` cannot fail.
+        let _ = write!(
+            body,
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"",
+            escape_quoted(name)
+        );
+
+        match part {
+            MultipartPart::Text(text) => {
+                let _ = write!(body, "\r\n\r\n{text}\r\n");
+            }
+            MultipartPart::Bytes {
+                data,
+                filename,
+                content_type,
+            } => {
+                if let Some(filename) = filename {
+                    let _ = write!(body, "; filename=\"{}\"", escape_quoted(filename));
+                }
+
+                let data = std::str::from_utf8(data).map_err(|error| NonUtf8PartError {
+                    name: name.clone(),
+                    error,
+                })?;
+
+                let _ = write!(
+                    body,
+                    "\r\nContent-Type: {}\r\n\r\n{data}\r\n",
+                    content_type
+                        .as_deref()
+                        .unwrap_or("application/octet-stream"),
+                );
+            }
+        }
+    }
+
+    let _ = write!(body, "--{boundary}--\r\n");
+
+    Ok((boundary, body))
+}
+
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultipartPart;
+    use crate::request::Request;
+
+    #[test]
+    fn multipart_body() -> Result<(), Box<dyn std::error::Error>> {
+        let mut request = Request::new(
+            "https://example.com/upload",
+            None,
+            Some(http::Method::POST),
+            Some([("content-type", "text/plain")]),
+            None::<String>,
+        )?;
+
+        request.multipart_body([
+            ("name".to_string(), MultipartPart::Text("value".to_string())),
+            (
+                "file".to_string(),
+                MultipartPart::Bytes {
+                    data: b"{}".to_vec(),
+                    filename: Some("data.json".to_string()),
+                    content_type: Some("application/json".to_string()),
+                },
+            ),
+        ])?;
+
+        assert_eq!(request.headers.len(), 1);
+        assert_eq!(
+            request.headers["Content-Type"],
+            "multipart/form-data; boundary=scraper-trail-boundary"
+        );
+        assert_eq!(
+            request.body.as_deref(),
+            Some(
+                "--scraper-trail-boundary\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nvalue\r\n\
+                 --scraper-trail-boundary\r\nContent-Disposition: form-data; name=\"file\"; filename=\"data.json\"\r\nContent-Type: application/json\r\n\r\n{}\r\n\
+                 --scraper-trail-boundary--\r\n"
+            )
+        );
+
+        let before = request.clone();
+        let error = request
+            .multipart_body([
+                ("name".to_string(), MultipartPart::Text("value".to_string())),
+                (
+                    "image".to_string(),
+                    MultipartPart::Bytes {
+                        data: vec![0x89, b'P', b'N', b'G'],
+                        filename: None,
+                        content_type: Some("image/png".to_string()),
+                    },
+                ),
+            ])
+            .unwrap_err();
+
+        assert_eq!(error.name, "image");
+        assert_eq!(request, before);
+
+        Ok(())
+    }
+}
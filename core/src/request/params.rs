@@ -1,6 +1,9 @@
+use bounded_static::IntoBoundedStatic;
 use chrono::{DateTime, Utc};
+use url::Url;
 
 use super::Request;
+use crate::exchange::Response;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
 pub enum ParseError {
@@ -32,4 +35,322 @@ impl ParseError {
 pub trait Params: Sized {
     fn build_request(&self, timestamp: Option<DateTime<Utc>>) -> Request<'_>;
     fn parse_request(request: &Request<'_>) -> Result<Self, ParseError>;
+
+    /// Complete the parameters parsed from the request using its response.
+    ///
+    /// This is useful for formats where some of the request's identity is only available in the
+    /// response (e.g. an echoed request ID). Archive entries only call this for
+    /// [`crate::archive::Archiveable`] types that provide a JSON view of their responses, and
+    /// always call [`Self::parse_request`] first, since its result is needed to read the
+    /// response. That call must therefore succeed on requests that are incomplete without their
+    /// responses, returning provisional parameters that are passed here as `provisional`. The
+    /// default implementation returns them unchanged.
+    fn parse_request_with_response(
+        provisional: Self,
+        _request: &Request<'_>,
+        _response: &Response<'_, serde_json::Value>,
+    ) -> Result<Self, ParseError> {
+        Ok(provisional)
+    }
+
+    /// Build the parameters for the next request from cookies set by the previous response.
+    ///
+    /// This supports session-based pagination, where the server tracks the position in a cookie
+    /// (see [`Response::set_cookies`]). The default implementation returns `None`.
+    fn next_from_cookies(&self, _cookies: &[crate::cookie::Cookie]) -> Option<Self> {
+        None
+    }
+
+    /// Describe the requests this type builds and parses.
+    ///
+    /// The default implementation returns an empty shape.
+    #[must_use]
+    fn describe() -> RequestShape {
+        RequestShape::default()
+    }
+}
+
+/// A description of the expected shape of a [`Params`] type's requests.
+///
+/// Empty fields mean that nothing is known about that part of the request.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RequestShape {
+    pub method: Option<http::Method>,
+    /// A human-readable URL pattern (e.g. `https://example.com/items/{id}`).
+    pub url_pattern: Option<&'static str>,
+    pub required_headers: Vec<&'static str>,
+    pub required_body_keys: Vec<&'static str>,
+}
+
+/// An object-safe view of [`Params`], allowing heterogeneous parameter types to be stored as
+/// `Box<dyn DynParams>`.
+pub trait DynParams {
+    /// Build a request using the current time as its timestamp.
+    fn build(&self) -> Request<'static>;
+    fn type_name(&self) -> &str;
+    fn shape(&self) -> RequestShape;
+}
+
+impl<P: Params> DynParams for P {
+    fn build(&self) -> Request<'static> {
+        self.build_request(None).into_static()
+    }
+
+    fn type_name(&self) -> &str {
+        std::any::type_name::<P>()
+    }
+
+    fn shape(&self) -> RequestShape {
+        P::describe()
+    }
+}
+
+/// Helper for building request URLs from a base URL and query parameters.
+///
+/// Any query parameters already present on the base URL are treated as defaults: setting a
+/// parameter with the same key replaces the existing value in place, while new keys are appended.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UrlBuilder {
+    base: Url,
+    params: Vec<(String, Option<String>)>,
+}
+
+impl UrlBuilder {
+    pub fn new<U: AsRef<str>>(base: U) -> Result<Self, url::ParseError> {
+        Ok(Self::from_url(base.as_ref().parse()?))
+    }
+
+    #[must_use]
+    pub fn from_url(mut base: Url) -> Self {
+        let params = base
+            .query()
+            .map(|query| {
+                query
+                    .split('&')
+                    .filter(|pair| !pair.is_empty())
+                    .map(|pair| {
+                        // Only the first `=` separates the key and value (the value may contain
+                        // more).
+                        let (key, value) = url::form_urlencoded::parse(pair.as_bytes())
+                            .next()
+                            .unwrap_or_default();
+
+                        (
+                            key.into_owned(),
+                            pair.contains('=').then(|| value.into_owned()),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        base.set_query(None);
+
+        Self { base, params }
+    }
+
+    /// Set a parameter, replacing any existing values for the key.
+    #[must_use]
+    pub fn param<K: Into<String>, V: Into<String>>(self, key: K, value: V) -> Self {
+        self.set(key.into(), Some(value.into()))
+    }
+
+    /// Set a parameter that has no value (e.g. `authuser` in `?hl=en&authuser`), replacing any
+    /// existing values for the key.
+    #[must_use]
+    pub fn flag<K: Into<String>>(self, key: K) -> Self {
+        self.set(key.into(), None)
+    }
+
+    /// Add a parameter value without replacing existing values for the key.
+    #[must_use]
+    pub fn append<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.params.push((key.into(), Some(value.into())));
+        self
+    }
+
+    #[must_use]
+    pub fn build(&self) -> Url {
+        let mut url = self.base.clone();
+
+        if !self.params.is_empty() {
+            let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+
+            for (key, value) in &self.params {
+                match value {
+                    Some(value) => serializer.append_pair(key, value),
+                    None => serializer.append_key_only(key),
+                };
+            }
+
+            url.set_query(Some(&serializer.finish()));
+        }
+
+        url
+    }
+
+    fn set(mut self, key: String, value: Option<String>) -> Self {
+        match self
+            .params
+            .iter()
+            .position(|(existing, _)| *existing == key)
+        {
+            Some(index) => {
+                let mut current = 0;
+
+                self.params.retain(|(existing, _)| {
+                    let keep = *existing != key || current == index;
+                    current += 1;
+                    keep
+                });
+                self.params[index].1 = value;
+            }
+            None => {
+                self.params.push((key, value));
+            }
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DynParams, Params, ParseError, RequestShape, UrlBuilder};
+    use crate::request::Request;
+
+    const GOOGLE_PLAY_01_EXAMPLE: &str = include_str!("../../../examples/google-play-01.json");
+
+    #[test]
+    fn url_builder_google_pagination() -> Result<(), Box<dyn std::error::Error>> {
+        let example: crate::exchange::Exchange<'_, serde_json::Value> =
+            serde_json::from_str(GOOGLE_PLAY_01_EXAMPLE)?;
+
+        let url = UrlBuilder::new("https://play.google.com/_/PlayStoreUi/data/batchexecute")?
+            .param("rpcids", "qnKhOb")
+            .param("f.sid", "-697906427155521722")
+            .param("bl", "boq_playuiserver_20190903.08_p0")
+            .param("hl", "en")
+            .param("gl", "us")
+            .flag("authuser")
+            .param("soc-app", "121")
+            .param("soc-platform", "1")
+            .param("soc-device", "1")
+            .param("_reqid", "1065213")
+            .build();
+
+        assert_eq!(url, example.request.url);
+
+        Ok(())
+    }
+
+    #[test]
+    fn url_builder_defaults() -> Result<(), Box<dyn std::error::Error>> {
+        let url = UrlBuilder::new("https://example.com/search?q=a+b&page=1&flag&page=2")?
+            .param("page", "3")
+            .append("tag", "x&y")
+            .build();
+
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/search?q=a+b&page=3&flag&tag=x%26y"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn url_builder_values_with_equals() -> Result<(), Box<dyn std::error::Error>> {
+        let url =
+            UrlBuilder::new("https://example.com/api?token=abc==&cursor=a%3Db%3D&empty=&flag")?
+                .param("page", "2")
+                .build();
+
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/api?token=abc%3D%3D&cursor=a%3Db%3D&empty=&flag&page=2"
+        );
+        assert_eq!(
+            url.query_pairs().collect::<Vec<_>>(),
+            vec![
+                ("token".into(), "abc==".into()),
+                ("cursor".into(), "a=b=".into()),
+                ("empty".into(), "".into()),
+                ("flag".into(), "".into()),
+                ("page".into(), "2".into()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn describe() {
+        let params: Vec<Box<dyn DynParams>> = vec![Box::new(Item(1)), Box::new(Search)];
+
+        assert_eq!(
+            params[0].shape(),
+            RequestShape {
+                method: Some(http::Method::GET),
+                url_pattern: Some("https://example.com/items/{id}"),
+                required_headers: vec!["accept"],
+                required_body_keys: vec![],
+            }
+        );
+        assert_eq!(params[1].shape(), RequestShape::default());
+        assert!(params[0].type_name().ends_with("::Item"));
+        assert_eq!(params[0].build().url.path(), "/items/1");
+    }
+
+    struct Item(u64);
+
+    impl Params for Item {
+        fn build_request(&self, timestamp: Option<chrono::DateTime<chrono::Utc>>) -> Request<'_> {
+            let mut request = Request::builder(format!("https://example.com/items/{}", self.0))
+                .header("accept", "application/json")
+                .build()
+                .expect("valid item URL");
+
+            if let Some(timestamp) = timestamp {
+                request.timestamp = timestamp;
+            }
+
+            request
+        }
+
+        fn parse_request(request: &Request<'_>) -> Result<Self, ParseError> {
+            request
+                .url
+                .path()
+                .strip_prefix("/items/")
+                .and_then(|id| id.parse().ok())
+                .map(Self)
+                .ok_or(ParseError::InvalidUrl {
+                    expected: "item request",
+                })
+        }
+
+        fn describe() -> RequestShape {
+            RequestShape {
+                method: Some(http::Method::GET),
+                url_pattern: Some("https://example.com/items/{id}"),
+                required_headers: vec!["accept"],
+                required_body_keys: vec![],
+            }
+        }
+    }
+
+    struct Search;
+
+    impl Params for Search {
+        fn build_request(&self, _timestamp: Option<chrono::DateTime<chrono::Utc>>) -> Request<'_> {
+            Request::builder("https://example.com/search")
+                .build()
+                .expect("valid search URL")
+        }
+
+        fn parse_request(_request: &Request<'_>) -> Result<Self, ParseError> {
+            Ok(Self)
+        }
+    }
 }
@@ -0,0 +1,134 @@
+use super::Request;
+use std::borrow::Cow;
+use url::Url;
+
+/// The value used in place of redacted header and query parameter values.
+pub const REDACTED: &str = "<redacted>";
+
+/// Header and query parameter names (lowercase) that are considered sensitive.
+pub const SENSITIVE_NAMES: &[&str] = &[
+    "access_token",
+    "api-key",
+    "api_key",
+    "apikey",
+    "authorization",
+    "client_secret",
+    "cookie",
+    "key",
+    "password",
+    "proxy-authorization",
+    "refresh_token",
+    "secret",
+    "session",
+    "set-cookie",
+    "token",
+    "x-api-key",
+    "x-auth-token",
+    "x-csrf-token",
+];
+
+/// Whether a header or query parameter name is on the sensitive deny-list (compared
+/// case-insensitively).
+#[must_use]
+pub fn is_sensitive_name(name: &str) -> bool {
+    SENSITIVE_NAMES
+        .iter()
+        .any(|sensitive| sensitive.eq_ignore_ascii_case(name))
+}
+
+/// Whether a name is in the given list (compared case-insensitively).
+pub(crate) fn matches_name(names: &[&str], name: &str) -> bool {
+    names
+        .iter()
+        .any(|candidate| candidate.eq_ignore_ascii_case(name))
+}
+
+impl Request<'_> {
+    /// Replace the values of the named headers (compared case-insensitively) with [`REDACTED`].
+    pub fn redact(&mut self, names: &[&str]) {
+        for (name, value) in &mut self.headers {
+            if matches_name(names, name) {
+                *value = Cow::Borrowed(REDACTED);
+            }
+        }
+    }
+
+    /// Consuming version of [`Self::redact`].
+    #[must_use]
+    pub fn redacted(mut self, names: &[&str]) -> Self {
+        self.redact(names);
+        self
+    }
+
+    /// The URL with the values of sensitive query parameters replaced.
+    #[must_use]
+    pub fn redacted_url(&self) -> Url {
+        let mut url = self.url.clone();
+
+        if let Some(query) = self.url.query() {
+            let query = query
+                .split('&')
+                .map(|segment| {
+                    let (key, _) = url::form_urlencoded::parse(segment.as_bytes())
+                        .next()
+                        .unwrap_or_default();
+
+                    match segment.split_once('=') {
+                        Some((raw_key, _)) if is_sensitive_name(&key) => Cow::Owned(format!(
+                            "{raw_key}={}",
+                            url::form_urlencoded::byte_serialize(REDACTED.as_bytes())
+                                .collect::<String>()
+                        )),
+                        _ => Cow::Borrowed(segment),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("&");
+
+            url.set_query(Some(&query));
+        }
+
+        url
+    }
+
+    /// The headers with the values of sensitive headers replaced.
+    pub(crate) fn redacted_headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers.iter().map(|(name, value)| {
+            (
+                name.as_ref(),
+                if is_sensitive_name(name) {
+                    REDACTED
+                } else {
+                    value.as_ref()
+                },
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::REDACTED;
+    use crate::request::Request;
+
+    #[test]
+    fn redact() -> Result<(), Box<dyn std::error::Error>> {
+        let request = Request::builder("https://example.com/api")
+            .timestamp("2025-01-01T00:00:00Z".parse()?)
+            .header("Authorization", "Bearer abc123")
+            .header("cookie", "session=1")
+            .header("Accept", "*/*")
+            .build()?
+            .redacted(&["authorization", "Cookie"]);
+
+        assert_eq!(request.header("authorization"), Some(REDACTED));
+        assert_eq!(request.header("cookie"), Some(REDACTED));
+        assert_eq!(request.header("accept"), Some("*/*"));
+
+        let round_tripped: Request<'_> = serde_json::from_str(&serde_json::to_string(&request)?)?;
+
+        assert_eq!(round_tripped, request);
+
+        Ok(())
+    }
+}